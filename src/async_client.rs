@@ -0,0 +1,268 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Async client
+
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use serde_json::Value;
+
+use crate::builder::{ClientBuilder, Timeout};
+use crate::jsonrpc;
+use crate::methods::impl_rpc_methods;
+use crate::params::Params;
+use crate::transport::{LimitedBody, Transport};
+use crate::{Auth, Error};
+
+/// Async version of [`Client`](crate::Client)
+///
+/// Methods, timeouts, retries and errors are the same of the blocking client.
+/// Requires a tokio runtime with the time driver enabled.
+#[derive(Debug, Clone)]
+pub struct AsyncClient {
+    transport: Transport,
+    /// Built at first request and shared by the clones, to reuse the connections
+    inner: Arc<OnceLock<reqwest::Client>>,
+}
+
+impl AsyncClient {
+    pub fn new(host: &str, username: &str, password: &str) -> Self {
+        ClientBuilder::new(host, username, password).build_async()
+    }
+
+    pub fn with_auth(host: &str, auth: Auth) -> Self {
+        ClientBuilder::with_auth(host, auth).build_async()
+    }
+
+    pub(crate) fn from_builder(builder: ClientBuilder) -> Self {
+        Self {
+            transport: Transport::new(builder),
+            inner: Arc::new(OnceLock::new()),
+        }
+    }
+
+    fn inner(&self) -> Result<&reqwest::Client, Error> {
+        if let Some(client) = self.inner.get() {
+            return Ok(client);
+        }
+
+        let client = self
+            .transport
+            .http
+            .apply_async(reqwest::Client::builder())
+            .build()?;
+        Ok(self.inner.get_or_init(|| client))
+    }
+
+    /// Post a request body and get the HTTP status and the response body
+    async fn post(&self, body: &str, timeout: Timeout) -> Result<(u16, String), Error> {
+        let (status, text) = self.post_once(body, timeout).await?;
+        if self.transport.reload_credentials(status) {
+            return self.post_once(body, timeout).await;
+        }
+        Ok((status, text))
+    }
+
+    async fn post_once(&self, body: &str, timeout: Timeout) -> Result<(u16, String), Error> {
+        let mut req = self
+            .inner()?
+            .post(self.transport.url())
+            .body(body.to_string());
+        if let Some((username, password)) = self.transport.credentials()? {
+            req = req.basic_auth(username, Some(password));
+        }
+        if let Some(timeout) = self.transport.timeout(timeout) {
+            req = req.timeout(timeout);
+        }
+        let mut res = req.send().await?;
+
+        let status: u16 = res.status().as_u16();
+        let text: String = match self.transport.http.max_response_bytes {
+            Some(max) => {
+                let mut body = LimitedBody::new(max, res.content_length())?;
+                while let Some(chunk) = res.chunk().await? {
+                    body.push(&chunk)?;
+                }
+                body.into_string()?
+            }
+            None => res.text().await?,
+        };
+        Ok((status, text))
+    }
+
+    async fn call_jsonrpc(
+        &self,
+        method: &str,
+        params: Params,
+        timeout: Timeout,
+    ) -> Result<String, Error> {
+        let param_count: usize = params.len();
+        let (id, body) = self.transport.prepare(method, params)?;
+
+        let mut attempt: u32 = 1;
+        loop {
+            let started = Instant::now();
+            let res = match self.post(&body, timeout).await {
+                Ok((status, text)) => self.transport.check_response(method, id, status, text),
+                Err(e) => Err(e),
+            };
+            self.transport
+                .notify(method, param_count, started.elapsed(), &res);
+            match self.transport.retry_delay(attempt, &res) {
+                Some(delay) => {
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => return res,
+            }
+        }
+    }
+
+    /// Send calls in a single batch request
+    ///
+    /// The outer `Result` is for transport errors, the inner ones for the single calls errors.
+    pub(crate) async fn call_batch(
+        &self,
+        calls: Vec<(String, Params)>,
+        timeout: Timeout,
+    ) -> Result<Vec<Result<Value, Error>>, Error> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let methods: Vec<String> = calls.iter().map(|(method, ..)| method.clone()).collect();
+        let body: String = jsonrpc::build_batch_body(calls)?;
+
+        let mut attempt: u32 = 1;
+        loop {
+            let started = Instant::now();
+            let res = match self.post(&body, timeout).await {
+                Ok((status, text)) => self.transport.check_batch_response(&methods, status, &text),
+                Err(e) => Err(e),
+            };
+            self.transport
+                .notify("batch", methods.len(), started.elapsed(), &res);
+            match self.transport.batch_retry_delay(attempt, &res) {
+                Some(delay) => {
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => return res,
+            }
+        }
+    }
+}
+
+impl_rpc_methods! {
+    client: AsyncClient,
+    asyncness: [async],
+    await: [.await],
+    examples: any,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use bitcoin::{BlockHash, Network};
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock::{fixture, MockServer};
+    use crate::{Chain, RetryPolicy};
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_get_blockchain_info() {
+        let server = MockServer::with_result(fixture("getblockchaininfo.json"));
+        let rpc = AsyncClient::new(server.url(), "username", "password");
+
+        let info = block_on(rpc.get_blockchain_info()).unwrap();
+        assert_eq!(info.chain, Chain::Network(Network::Bitcoin));
+        assert_eq!(info.blocks, 850_000);
+        assert_eq!(server.last_request().method(), "getblockchaininfo");
+    }
+
+    #[test]
+    fn test_get_block() {
+        let server = MockServer::with_result(fixture("getblock_verbosity2.json"));
+        let rpc = AsyncClient::new(server.url(), "username", "password");
+        let hash =
+            BlockHash::from_str("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+                .unwrap();
+
+        let block = block_on(rpc.get_block(&hash)).unwrap();
+        assert_eq!(block.block_hash(), hash);
+        assert_eq!(block.txdata.len(), 1);
+        assert_eq!(
+            server.last_request().params(),
+            &json!([hash.to_string(), 2])
+        );
+    }
+
+    #[test]
+    fn test_retry() {
+        let calls = AtomicUsize::new(0);
+        let server = MockServer::start(move |req| match calls.fetch_add(1, Ordering::SeqCst) {
+            0 => req.error(-28, "Loading block index..."),
+            _ => req.result(json!(1)),
+        });
+        let rpc = ClientBuilder::new(server.url(), "username", "password")
+            .retry(RetryPolicy {
+                base_delay: Duration::from_millis(1),
+                ..Default::default()
+            })
+            .build_async();
+
+        assert_eq!(block_on(rpc.get_block_count()).unwrap(), 1);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_same_types_of_blocking_client() {
+        let server = MockServer::start(|req| match req.method() {
+            "getblockchaininfo" => req.result(fixture("getblockchaininfo.json")),
+            _ => req.result(fixture("getblock_verbosity1.json")),
+        });
+        let rpc = AsyncClient::new(server.url(), "username", "password");
+        let blocking = crate::Client::new(server.url(), "username", "password");
+        let hash =
+            BlockHash::from_str("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+                .unwrap();
+
+        // Both clients deserialize the same fixtures identically
+        assert_eq!(
+            format!("{:?}", block_on(rpc.get_blockchain_info()).unwrap()),
+            format!("{:?}", blocking.get_blockchain_info().unwrap())
+        );
+        assert_eq!(
+            format!("{:?}", block_on(rpc.get_block_info(&hash)).unwrap()),
+            format!("{:?}", blocking.get_block_info(&hash).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_clones_share_connections() {
+        let server = MockServer::with_result(json!(850_000));
+        let rpc = AsyncClient::new(server.url(), "username", "password");
+
+        block_on(async {
+            rpc.get_block_count().await.unwrap();
+            rpc.clone().get_block_count().await.unwrap();
+            rpc.wallet("hot").get_block_count().await.unwrap();
+        });
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(server.connections(), 1);
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Authentication
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::Error;
+
+/// Read `<username>:<password>` from a Bitcoin Core `.cookie` file
+pub(crate) fn read_cookie(path: &Path) -> Result<(String, String), Error> {
+    let cookie: String = fs::read_to_string(path)?;
+    match cookie.trim_end().split_once(':') {
+        Some((username, password)) if !username.is_empty() => {
+            Ok((username.to_string(), password.to_string()))
+        }
+        _ => Err(Error::InvalidCookie),
+    }
+}
+
+/// Credentials
+#[derive(Clone, PartialEq, Eq)]
+pub enum Auth {
+    /// No `Authorization` header (i.e. added by a reverse proxy)
+    None,
+    UserPass {
+        username: String,
+        password: String,
+    },
+    /// Bitcoin Core `.cookie` file, read again when rejected since it's rewritten at every restart
+    CookieFile(PathBuf),
+}
+
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("None"),
+            Self::UserPass { username, .. } => f
+                .debug_struct("UserPass")
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+            Self::CookieFile(path) => f.debug_tuple("CookieFile").field(path).finish(),
+        }
+    }
+}
+
+impl Auth {
+    /// Get `(username, password)`, if any
+    pub(crate) fn credentials(&self) -> Result<Option<(String, String)>, Error> {
+        match self {
+            Self::None => Ok(None),
+            Self::UserPass { username, password } => Ok(Some((username.clone(), password.clone()))),
+            Self::CookieFile(path) => read_cookie(path).map(Some),
+        }
+    }
+}
+
+/// [`Auth`] with the cookie cached, shared by the clones of a client
+#[derive(Clone)]
+pub(crate) struct Credentials {
+    auth: Auth,
+    cookie: Arc<Mutex<Option<(String, String)>>>,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.auth.fmt(f)
+    }
+}
+
+impl Credentials {
+    pub fn new(auth: Auth) -> Self {
+        Self {
+            auth,
+            cookie: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn is_cookie(&self) -> bool {
+        matches!(self.auth, Auth::CookieFile(..))
+    }
+
+    /// Get `(username, password)`, reading the cookie file only if not cached
+    pub fn get(&self) -> Result<Option<(String, String)>, Error> {
+        if !self.is_cookie() {
+            return self.auth.credentials();
+        }
+
+        let mut cookie = self.cookie.lock().unwrap_or_else(|e| e.into_inner());
+        if cookie.is_none() {
+            *cookie = self.auth.credentials()?;
+        }
+        Ok(cookie.clone())
+    }
+
+    /// Drop the cached cookie, to read the file again at next request
+    pub fn reload(&self) {
+        *self.cookie.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::temp_file;
+
+    #[test]
+    fn test_read_cookie() {
+        let path = temp_file("read.cookie", "__cookie__:a1b2c3\n");
+        assert_eq!(
+            read_cookie(&path).unwrap(),
+            (String::from("__cookie__"), String::from("a1b2c3"))
+        );
+
+        // The password may contain `:`
+        let path = temp_file("colon.cookie", "__cookie__:a1:b2");
+        assert_eq!(read_cookie(&path).unwrap().1, "a1:b2");
+
+        let path = temp_file("invalid.cookie", ":a1b2c3");
+        assert!(matches!(read_cookie(&path), Err(Error::InvalidCookie)));
+        let path = temp_file("empty.cookie", "");
+        assert!(matches!(read_cookie(&path), Err(Error::InvalidCookie)));
+
+        let path = std::env::temp_dir().join("bitcoin-rpc-missing.cookie");
+        assert!(matches!(read_cookie(&path), Err(Error::Io(..))));
+    }
+}
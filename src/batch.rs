@@ -0,0 +1,166 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! JSON-RPC batch requests
+
+use std::marker::PhantomData;
+
+use bitcoin::{BlockHash, Txid};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::jsonrpc;
+use crate::{Error, Params};
+
+/// Handle to the result of a batched call
+#[derive(Debug)]
+pub struct BatchHandle<T> {
+    index: usize,
+    _result: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for BatchHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BatchHandle<T> {}
+
+/// Batch of calls, sent in a single request with [`Client::send_batch`](crate::Client::send_batch)
+///
+/// ```rust,no_run
+/// use bitcoin_rpc::{Batch, Client};
+///
+/// let rpc = Client::new("http://127.0.0.1:8332", "username", "password");
+/// let mut batch = Batch::new();
+/// let handles: Vec<_> = (0..1000).map(|height| batch.get_block_hash(height)).collect();
+/// let mut res = rpc.send_batch(batch).unwrap();
+/// for handle in handles {
+///     println!("{:?}", res.take(handle));
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Batch {
+    pub(crate) calls: Vec<(String, Params)>,
+}
+
+impl Batch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue any RPC
+    pub fn call<T>(&mut self, method: &str, params: Params) -> BatchHandle<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.calls.push((method.to_string(), params));
+        BatchHandle {
+            index: self.calls.len() - 1,
+            _result: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    pub fn get_block_count(&mut self) -> BatchHandle<u64> {
+        self.call("getblockcount", Params::new())
+    }
+
+    pub fn get_best_block_hash(&mut self) -> BatchHandle<BlockHash> {
+        self.call("getbestblockhash", Params::new())
+    }
+
+    pub fn get_block_hash(&mut self, block_height: u64) -> BatchHandle<BlockHash> {
+        self.call("getblockhash", Params::new().push(block_height))
+    }
+
+    /// Queue a `getrawtransaction`, getting the consensus-encoded hex
+    pub fn get_raw_transaction_hex(&mut self, txid: &Txid) -> BatchHandle<String> {
+        self.call("getrawtransaction", Params::new().push(txid).push(false))
+    }
+}
+
+/// Results of a [`Batch`]
+#[derive(Debug)]
+pub struct BatchResponse {
+    results: Vec<Result<Value, Error>>,
+}
+
+impl BatchResponse {
+    pub(crate) fn new(results: Vec<Result<Value, Error>>) -> Self {
+        Self { results }
+    }
+
+    /// Take the result of a call
+    ///
+    /// Return [`Error::BadResult`] if already taken or if the handle belongs to another batch.
+    pub fn take<T>(&mut self, handle: BatchHandle<T>) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let res = self.results.get_mut(handle.index).ok_or(Error::BadResult)?;
+        let value: Value = std::mem::replace(res, Err(Error::BadResult))?;
+        jsonrpc::deserialize_result(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Response of a batch with a single `method` call, replied with `item`
+    fn single_response(method: &str, item: &str) -> BatchResponse {
+        let data: String = format!("[{item}]");
+        BatchResponse::new(jsonrpc::deserialize_batch(&[method.to_string()], &data).unwrap())
+    }
+
+    #[test]
+    fn test_take_null() {
+        let item: &str = r#"{"result":null,"error":null,"id":0}"#;
+
+        // Same error of a single call
+        assert!(matches!(
+            jsonrpc::deserialize::<u64>("getblockcount", item),
+            Err(Error::NullResult)
+        ));
+        let mut batch = Batch::new();
+        let count = batch.get_block_count();
+        let mut res = single_response("getblockcount", item);
+        assert!(matches!(res.take(count), Err(Error::NullResult)));
+        // Already taken
+        assert!(matches!(res.take(count), Err(Error::BadResult)));
+
+        // `null` is a valid `Option`
+        let mut batch = Batch::new();
+        let tx_out = batch.call::<Option<u64>>("gettxout", Params::new());
+        let mut res = single_response("gettxout", item);
+        assert_eq!(res.take(tx_out).unwrap(), None);
+    }
+
+    #[test]
+    fn test_take_invalid() {
+        let mut batch = Batch::new();
+        let count = batch.get_block_count();
+
+        let mut res = single_response(
+            "getblockcount",
+            r#"{"result":"850000","error":null,"id":0}"#,
+        );
+        assert!(matches!(
+            res.take(count),
+            Err(Error::FailedToDeserialize(..))
+        ));
+
+        // Missing `result`
+        let mut res = single_response("getblockcount", r#"{"error":null,"id":0}"#);
+        assert!(matches!(res.take(count), Err(Error::BadResult)));
+    }
+}
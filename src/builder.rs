@@ -0,0 +1,245 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "async")]
+use crate::AsyncClient;
+use crate::{Auth, Client, Error, RetryPolicy};
+
+/// HTTP transport options
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpOptions {
+    pub connect_timeout: Option<Duration>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub tcp_keepalive: Option<Duration>,
+    pub http1_only: bool,
+    pub max_response_bytes: Option<usize>,
+}
+
+impl HttpOptions {
+    pub fn apply(
+        &self,
+        mut builder: reqwest::blocking::ClientBuilder,
+    ) -> reqwest::blocking::ClientBuilder {
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if self.http1_only {
+            builder = builder.http1_only();
+        }
+        builder
+    }
+
+    #[cfg(feature = "async")]
+    pub fn apply_async(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if self.http1_only {
+            builder = builder.http1_only();
+        }
+        builder
+    }
+}
+
+/// Per-request timeouts (`None` for no timeout)
+///
+/// Calls that may legitimately run for hours (i.e. `rescanblockchain`) never time out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    /// Calls without a specific timeout (default: `None`)
+    pub default: Option<Duration>,
+    /// Calls reading blocks from disk or returning big responses, like `getblock` and
+    /// `getrawtransaction` (default: 120 secs)
+    pub block_fetch: Option<Duration>,
+    /// Calls scanning the whole UTXO set, like `gettxoutsetinfo` (default: 1800 secs)
+    pub heavy_calls: Option<Duration>,
+}
+
+impl TimeoutConfig {
+    /// Get the HTTP timeout of a request
+    pub(crate) fn resolve(&self, timeout: Timeout) -> Option<Duration> {
+        match timeout {
+            Timeout::Default => self.default,
+            Timeout::Unlimited => None,
+            Timeout::After(timeout) => Some(timeout),
+        }
+    }
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            default: None,
+            block_fetch: Some(Duration::from_secs(120)),
+            heavy_calls: Some(Duration::from_secs(1800)),
+        }
+    }
+}
+
+/// Timeout of a single request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Timeout {
+    /// [`TimeoutConfig::default`]
+    Default,
+    /// No timeout, for calls that may run for hours
+    Unlimited,
+    After(Duration),
+}
+
+impl From<Option<Duration>> for Timeout {
+    /// `None` is [`Timeout::Unlimited`], as in [`TimeoutConfig`]
+    fn from(timeout: Option<Duration>) -> Self {
+        match timeout {
+            Some(timeout) => Self::After(timeout),
+            None => Self::Unlimited,
+        }
+    }
+}
+
+/// Callback called after each request, with method name, duration and outcome
+///
+/// Params and credentials aren't passed, to avoid leaking secrets (i.e. wallet passphrases).
+#[derive(Clone)]
+pub(crate) struct RequestHook(Arc<RequestHookFn>);
+
+type RequestHookFn = dyn Fn(&str, Duration, Result<(), &Error>) + Send + Sync;
+
+impl RequestHook {
+    pub fn call(&self, method: &str, duration: Duration, result: Result<(), &Error>) {
+        (self.0)(method, duration, result)
+    }
+}
+
+impl fmt::Debug for RequestHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RequestHook")
+    }
+}
+
+/// [`Client`] builder
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    pub(crate) host: String,
+    pub(crate) auth: Auth,
+    pub(crate) http: HttpOptions,
+    pub(crate) timeouts: TimeoutConfig,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) on_request: Option<RequestHook>,
+}
+
+impl ClientBuilder {
+    pub fn new(host: &str, username: &str, password: &str) -> Self {
+        Self::with_auth(
+            host,
+            Auth::UserPass {
+                username: username.into(),
+                password: password.into(),
+            },
+        )
+    }
+
+    pub fn with_auth(host: &str, auth: Auth) -> Self {
+        Self {
+            host: host.into(),
+            auth,
+            http: HttpOptions::default(),
+            timeouts: TimeoutConfig::default(),
+            retry: None,
+            on_request: None,
+        }
+    }
+
+    /// Timeout for the connect phase only
+    ///
+    /// Independent of the total per-request timeouts (i.e. a dead host fails fast even for long calls).
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.http.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout of the calls without a specific one (see [`TimeoutConfig::default`])
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.default = Some(timeout);
+        self
+    }
+
+    /// Override all the per-request timeouts
+    pub fn timeouts(mut self, timeouts: TimeoutConfig) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Retry the transient failures
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Max idle connections kept open per host
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.http.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// TCP keep-alive interval
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.http.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Only use HTTP/1 (some proxies mishandle HTTP/2)
+    pub fn http1_only(mut self) -> Self {
+        self.http.http1_only = true;
+        self
+    }
+
+    /// Max size of the response body (see [`Error::ResponseTooLarge`](crate::Error::ResponseTooLarge))
+    pub fn max_response_bytes(mut self, max: usize) -> Self {
+        self.http.max_response_bytes = Some(max);
+        self
+    }
+
+    /// Set a callback called after each request (batches use the `batch` method name)
+    ///
+    /// ```rust,no_run
+    /// use bitcoin_rpc::Client;
+    ///
+    /// let rpc = Client::builder("http://127.0.0.1:8332", "username", "password")
+    ///     .on_request(|method, duration, result| {
+    ///         println!("{method} took {duration:?}: {result:?}");
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_request<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, Duration, Result<(), &Error>) + Send + Sync + 'static,
+    {
+        self.on_request = Some(RequestHook(Arc::new(f)));
+        self
+    }
+
+    pub fn build(self) -> Client {
+        Client::from_builder(self)
+    }
+
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> AsyncClient {
+        AsyncClient::from_builder(self)
+    }
+}
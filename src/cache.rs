@@ -0,0 +1,141 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Client with cached responses
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use bitcoin::BlockHash;
+
+use crate::{BlockchainInfo, Client, Error};
+
+struct Cached<T> {
+    value: Mutex<Option<(Instant, T)>>,
+}
+
+impl<T> Cached<T>
+where
+    T: Clone,
+{
+    fn new() -> Self {
+        Self {
+            value: Mutex::new(None),
+        }
+    }
+
+    fn get_or_fetch<F>(&self, ttl: Duration, fetch: F) -> Result<T, Error>
+    where
+        F: FnOnce() -> Result<T, Error>,
+    {
+        let mut value = self.value.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some((fetched_at, value)) = value.as_ref() {
+            if fetched_at.elapsed() < ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        let fetched: T = fetch()?;
+        *value = Some((Instant::now(), fetched.clone()));
+        Ok(fetched)
+    }
+}
+
+/// Client that caches the responses of some idempotent read calls for a TTL
+///
+/// Cached calls: [`CachingClient::get_blockchain_info`], [`CachingClient::get_block_count`]
+/// and [`CachingClient::get_best_block_hash`]. Invalidation is only time-based.
+/// Use [`CachingClient::client`] for all the other calls.
+pub struct CachingClient {
+    client: Client,
+    ttl: Duration,
+    blockchain_info: Cached<BlockchainInfo>,
+    block_count: Cached<u64>,
+    best_block_hash: Cached<BlockHash>,
+}
+
+impl CachingClient {
+    pub fn new(client: Client, ttl: Duration) -> Self {
+        Self {
+            client,
+            ttl,
+            blockchain_info: Cached::new(),
+            block_count: Cached::new(),
+            best_block_hash: Cached::new(),
+        }
+    }
+
+    /// Get the inner (not cached) client
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn get_blockchain_info(&self) -> Result<BlockchainInfo, Error> {
+        self.blockchain_info
+            .get_or_fetch(self.ttl, || self.client.get_blockchain_info())
+    }
+
+    pub fn get_block_count(&self) -> Result<u64, Error> {
+        self.block_count
+            .get_or_fetch(self.ttl, || self.client.get_block_count())
+    }
+
+    pub fn get_best_block_hash(&self) -> Result<BlockHash, Error> {
+        self.best_block_hash
+            .get_or_fetch(self.ttl, || self.client.get_best_block_hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::thread;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock::{fixture, MockServer};
+
+    #[test]
+    fn test_ttl() {
+        let height = AtomicU64::new(100);
+        let server = MockServer::start(move |req| match req.method() {
+            "getblockcount" => req.result(json!(height.fetch_add(1, Ordering::SeqCst))),
+            _ => req.result(fixture("getblockchaininfo.json")),
+        });
+        let rpc = CachingClient::new(
+            Client::new(server.url(), "username", "password"),
+            Duration::from_millis(200),
+        );
+
+        assert_eq!(rpc.get_block_count().unwrap(), 100);
+        assert_eq!(rpc.get_block_count().unwrap(), 100);
+        assert_eq!(server.requests().len(), 1);
+
+        // Cached separately
+        assert_eq!(rpc.get_blockchain_info().unwrap().blocks, 850_000);
+        rpc.get_blockchain_info().unwrap();
+        assert_eq!(server.requests().len(), 2);
+
+        // Not cached
+        rpc.client().get_block_count().unwrap();
+        assert_eq!(server.requests().len(), 3);
+
+        thread::sleep(Duration::from_millis(250));
+        assert_eq!(rpc.get_block_count().unwrap(), 102);
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[test]
+    fn test_errors_not_cached() {
+        let server = MockServer::start(|req| req.error(-28, "Loading block index..."));
+        let rpc = CachingClient::new(
+            Client::new(server.url(), "username", "password"),
+            Duration::from_secs(60),
+        );
+        assert!(rpc.get_best_block_hash().is_err());
+        assert!(rpc.get_best_block_hash().is_err());
+        assert_eq!(server.requests().len(), 2);
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Cancellation of in-flight requests
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Waker = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct State {
+    cancelled: bool,
+    next_id: u64,
+    /// Called on cancellation, to wake the waiting requests
+    wakers: HashMap<u64, Waker>,
+}
+
+/// Token to cancel in-flight requests (see [`Client::call_cancellable`](crate::Client::call_cancellable))
+///
+/// Clones share the same state.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    state: Arc<Mutex<State>>,
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        let wakers: HashMap<u64, Waker> = {
+            let mut state = self.state();
+            state.cancelled = true;
+            std::mem::take(&mut state.wakers)
+        };
+        for waker in wakers.into_values() {
+            waker();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state().cancelled
+    }
+
+    /// Call `waker` on cancellation, until the returned [`Registration`] is dropped
+    ///
+    /// Return `None` if already cancelled.
+    pub(crate) fn register<F>(&self, waker: F) -> Option<Registration>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut state = self.state();
+        if state.cancelled {
+            return None;
+        }
+        let id: u64 = state.next_id;
+        state.next_id += 1;
+        state.wakers.insert(id, Box::new(waker));
+        Some(Registration {
+            state: self.state.clone(),
+            id,
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Waker registered in a [`CancelToken`], removed on drop
+pub(crate) struct Registration {
+    state: Arc<Mutex<State>>,
+    id: u64,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.wakers.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_wakers() {
+        let token = CancelToken::new();
+        let woken = Arc::new(AtomicUsize::new(0));
+
+        let counter = woken.clone();
+        let registration = token.register(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert!(registration.is_some());
+
+        // Dropped registrations aren't woken
+        let counter = woken.clone();
+        drop(token.register(move || {
+            counter.fetch_add(10, Ordering::SeqCst);
+        }));
+
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(woken.load(Ordering::SeqCst), 1);
+        assert!(token.register(|| ()).is_none());
+    }
+}
@@ -0,0 +1,2221 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Instant;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::auth;
+use crate::builder::{ClientBuilder, Timeout};
+use crate::cancel::CancelToken;
+use crate::jsonrpc;
+use crate::methods::impl_rpc_methods;
+use crate::params::Params;
+use crate::transport::{LimitedBody, Transport};
+use crate::{Auth, Error};
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    transport: Transport,
+    /// Built at first request and shared by the clones, to reuse the connections
+    inner: Arc<OnceLock<reqwest::blocking::Client>>,
+}
+
+impl Client {
+    pub fn new(host: &str, username: &str, password: &str) -> Self {
+        Self::builder(host, username, password).build()
+    }
+
+    pub fn with_auth(host: &str, auth: Auth) -> Self {
+        ClientBuilder::with_auth(host, auth).build()
+    }
+
+    /// Create a client authenticated with the `.cookie` file of Bitcoin Core
+    ///
+    /// The cookie is cached and read again if the node rejects it, i.e. after a restart.
+    pub fn new_with_cookie(host: &str, cookie_path: &Path) -> Result<Self, Error> {
+        auth::read_cookie(cookie_path)?;
+        Ok(Self::with_auth(
+            host,
+            Auth::CookieFile(cookie_path.to_path_buf()),
+        ))
+    }
+
+    pub fn builder(host: &str, username: &str, password: &str) -> ClientBuilder {
+        ClientBuilder::new(host, username, password)
+    }
+
+    pub(crate) fn from_builder(builder: ClientBuilder) -> Self {
+        Self {
+            transport: Transport::new(builder),
+            inner: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Get the HTTP client, building it if needed
+    ///
+    /// No timeout is set here: timeouts are set per request.
+    fn inner(&self) -> Result<&reqwest::blocking::Client, Error> {
+        if let Some(client) = self.inner.get() {
+            return Ok(client);
+        }
+
+        let client = self
+            .transport
+            .http
+            .apply(reqwest::blocking::Client::builder())
+            .timeout(None)
+            .build()?;
+        Ok(self.inner.get_or_init(|| client))
+    }
+
+    /// Post a request body and get the HTTP status and the response body
+    fn post(&self, body: &str, timeout: Timeout) -> Result<(u16, String), Error> {
+        let (status, text) = self.post_once(body, timeout)?;
+        if self.transport.reload_credentials(status) {
+            return self.post_once(body, timeout);
+        }
+        Ok((status, text))
+    }
+
+    fn post_once(&self, body: &str, timeout: Timeout) -> Result<(u16, String), Error> {
+        let client: &reqwest::blocking::Client = self.inner()?;
+
+        let mut req = client.post(self.transport.url()).body(body.to_string());
+        if let Some((username, password)) = self.transport.credentials()? {
+            req = req.basic_auth(username, Some(password));
+        }
+        if let Some(timeout) = self.transport.timeout(timeout) {
+            req = req.timeout(timeout);
+        }
+        let mut res = req.send()?;
+
+        let status: u16 = res.status().as_u16();
+        let text: String = match self.transport.http.max_response_bytes {
+            Some(max) => {
+                let mut body = LimitedBody::new(max, res.content_length())?;
+                let mut buf = [0u8; 8192];
+                loop {
+                    let len: usize = res.read(&mut buf)?;
+                    if len == 0 {
+                        break;
+                    }
+                    body.push(&buf[..len])?;
+                }
+                body.into_string()?
+            }
+            None => res.text()?,
+        };
+        Ok((status, text))
+    }
+
+    fn call_jsonrpc(
+        &self,
+        method: &str,
+        params: Params,
+        timeout: Timeout,
+    ) -> Result<String, Error> {
+        let param_count: usize = params.len();
+        let (id, body) = self.transport.prepare(method, params)?;
+
+        let mut attempt: u32 = 1;
+        loop {
+            let started = Instant::now();
+            let res = self
+                .post(&body, timeout)
+                .and_then(|(status, text)| self.transport.check_response(method, id, status, text));
+            self.transport
+                .notify(method, param_count, started.elapsed(), &res);
+            match self.transport.retry_delay(attempt, &res) {
+                Some(delay) => {
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                None => return res,
+            }
+        }
+    }
+
+    /// Send calls in a single batch request
+    ///
+    /// The outer `Result` is for transport errors, the inner ones for the single calls errors.
+    pub(crate) fn call_batch(
+        &self,
+        calls: Vec<(String, Params)>,
+        timeout: Timeout,
+    ) -> Result<Vec<Result<Value, Error>>, Error> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let methods: Vec<String> = calls.iter().map(|(method, ..)| method.clone()).collect();
+        let body: String = jsonrpc::build_batch_body(calls)?;
+
+        let mut attempt: u32 = 1;
+        loop {
+            let started = Instant::now();
+            let res = self.post(&body, timeout).and_then(|(status, text)| {
+                self.transport.check_batch_response(&methods, status, &text)
+            });
+            self.transport
+                .notify("batch", methods.len(), started.elapsed(), &res);
+            match self.transport.batch_retry_delay(attempt, &res) {
+                Some(delay) => {
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                None => return res,
+            }
+        }
+    }
+
+    /// Call any RPC, aborting as soon as `token` is cancelled
+    ///
+    /// The request runs on a separate thread, which is detached on cancellation.
+    /// The node keeps doing the work anyway (i.e. a `gettxoutsetinfo` scan isn't stopped).
+    pub fn call_cancellable<R>(
+        &self,
+        method: &str,
+        params: Params,
+        token: &CancelToken,
+    ) -> Result<R, Error>
+    where
+        R: DeserializeOwned + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+
+        let cancel_tx = tx.clone();
+        let Some(_registration) = token.register(move || {
+            let _ = cancel_tx.send(None);
+        }) else {
+            return Err(Error::Cancelled);
+        };
+
+        let client: Client = self.clone();
+        let method: String = method.to_string();
+        thread::spawn(move || {
+            let res = panic::catch_unwind(AssertUnwindSafe(|| client.call::<R>(&method, params)));
+            let _ = tx.send(Some(res.unwrap_or(Err(Error::Disconnected))));
+        });
+
+        // `None` is sent on cancellation
+        match rx.recv() {
+            Ok(Some(res)) => res,
+            Ok(None) => Err(Error::Cancelled),
+            Err(..) => Err(Error::Disconnected),
+        }
+    }
+}
+
+impl_rpc_methods! {
+    client: Client,
+    asyncness: [],
+    await: [],
+    examples: all,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use bitcoin::blockdata::constants::genesis_block;
+    use bitcoin::consensus::encode;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{
+        Address, Amount, Block, BlockHash, FeeRate, Network, PrivateKey, Psbt, PublicKey,
+        ScriptBuf, SignedAmount, Transaction, TxMerkleNode, Txid,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::methods::locator_heights;
+    use crate::mock::{fixture, temp_file, MockResponse, MockServer};
+    use crate::types::{
+        BlockStatsTarget, EstimateMode, GetBlockResponse, HashOrHeight, ImportMultiOptions,
+        ImportMultiRequest, ImportMultiScriptPubKey, ImportMultiTimestamp, PrevTx,
+        SendToAddressOptions, SighashType, TxOutSetHashType, VerboseBlockTransaction,
+        VerboseBlockTx,
+    };
+    use crate::{
+        AddressType, Batch, CoreFeature, LabelPurpose, RetryPolicy, Secret, TimeoutConfig,
+    };
+
+    fn client(server: &MockServer) -> Client {
+        Client::new(server.url(), "username", "password")
+    }
+
+    fn is_timeout(e: &Error) -> bool {
+        matches!(e, Error::Reqwest(e) if e.is_timeout())
+    }
+
+    #[test]
+    fn test_default_timeout() {
+        let server =
+            MockServer::start(|req| req.result(json!(1)).delay(Duration::from_millis(300)));
+
+        let mut rpc = client(&server);
+        assert_eq!(rpc.get_block_count().unwrap(), 1);
+
+        rpc.set_default_timeout(Duration::from_millis(50));
+        assert!(is_timeout(&rpc.get_block_count().unwrap_err()));
+    }
+
+    #[test]
+    fn test_unlimited_timeout_config() {
+        let server =
+            MockServer::start(|req| req.result(json!("00")).delay(Duration::from_millis(300)));
+
+        let rpc = client(&server).timeouts(TimeoutConfig {
+            default: Some(Duration::from_millis(50)),
+            block_fetch: None,
+            heavy_calls: None,
+        });
+        let hash =
+            BlockHash::from_str("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+                .unwrap();
+        assert_eq!(rpc.get_block_hex(&hash).unwrap(), "00");
+        assert!(is_timeout(&rpc.get_difficulty().unwrap_err()));
+    }
+
+    #[test]
+    fn test_with_timeout() {
+        let server = MockServer::start(|req| {
+            req.result(json!({ "start_height": 0, "stop_height": 10 }))
+                .delay(Duration::from_millis(300))
+        });
+        let rpc = Client::builder(server.url(), "username", "password")
+            .default_timeout(Duration::from_millis(50))
+            .build();
+
+        // Longer than the default
+        let slow = rpc.with_timeout(Duration::from_secs(5));
+        assert_eq!(slow.rescan_blockchain(None, None).unwrap().stop_height, 10);
+        slow.call_value("getblockcount", Params::new()).unwrap();
+
+        // Also for the calls without timeout
+        let fast = rpc.with_timeout(Duration::from_millis(50));
+        assert!(is_timeout(&fast.rescan_blockchain(None, None).unwrap_err()));
+
+        // The original client is untouched
+        assert!(is_timeout(
+            &rpc.call_value("getblockcount", Params::new()).unwrap_err()
+        ));
+    }
+
+    #[test]
+    fn test_rescan_without_timeout() {
+        let server = MockServer::start(|req| {
+            req.result(json!({ "start_height": 0, "stop_height": 10 }))
+                .delay(Duration::from_millis(300))
+        });
+
+        let rpc = Client::builder(server.url(), "username", "password")
+            .default_timeout(Duration::from_millis(50))
+            .build();
+        let res = rpc.rescan_blockchain(None, None).unwrap();
+        assert_eq!(res.stop_height, 10);
+    }
+
+    #[test]
+    fn test_wait_for_new_block_without_timeout() {
+        let server = MockServer::start(|req| {
+            req.result(json!({
+                "hash": "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+                "height": 0,
+            }))
+            .delay(Duration::from_millis(300))
+        });
+
+        let rpc = Client::builder(server.url(), "username", "password")
+            .default_timeout(Duration::from_millis(50))
+            .build();
+        assert_eq!(rpc.wait_for_new_block(None).unwrap().height, 0);
+        assert_eq!(rpc.wait_for_new_block(Some(0)).unwrap().height, 0);
+        assert_eq!(server.last_request().params(), &json!([0]));
+    }
+
+    #[test]
+    fn test_list_since_block_reorg() {
+        let server = MockServer::with_result(fixture("listsinceblock_reorg.json"));
+        let rpc = client(&server);
+        let hash =
+            BlockHash::from_str("6e1f2a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f7")
+                .unwrap();
+
+        let res = rpc.list_since_block(Some(&hash), None, None, None).unwrap();
+        assert_eq!(server.last_request().params(), &json!([hash]));
+        assert_eq!(res.transactions.len(), 1);
+        assert_eq!(res.transactions[0].block_height, Some(103));
+        // The transaction of the disconnected block
+        assert_eq!(res.removed.len(), 1);
+        assert_eq!(
+            res.removed[0].txid,
+            Txid::from_str(&"b2".repeat(32)).unwrap()
+        );
+        assert_eq!(res.removed[0].confirmations, 0);
+        assert_eq!(res.removed[0].block_hash, None);
+        assert_eq!(res.removed[0].amount, SignedAmount::from_sat(125_000_000));
+        assert_eq!(res.last_block, res.transactions[0].block_hash.unwrap());
+    }
+
+    #[test]
+    fn test_on_request() {
+        let server = MockServer::start(|req| match req.method() {
+            "getblockcount" => req.result(json!(850_000)).delay(Duration::from_millis(50)),
+            "getblock" => req.error(-5, "Block not found"),
+            _ => req.batch(|_, _| Ok(json!(hash_at(1)))),
+        });
+        let calls: Arc<Mutex<Vec<(String, Duration, bool)>>> = Arc::new(Mutex::new(Vec::new()));
+        let hook_calls = calls.clone();
+        let rpc = Client::builder(server.url(), "username", "password")
+            .on_request(move |method, duration, res| {
+                hook_calls
+                    .lock()
+                    .unwrap()
+                    .push((method.to_string(), duration, res.is_ok()));
+            })
+            .build();
+
+        rpc.get_block_count().unwrap();
+        rpc.get_block(&BlockHash::all_zeros()).unwrap_err();
+        let mut batch = Batch::new();
+        batch.call::<BlockHash>("getblockhash", Params::new().push(1));
+        rpc.send_batch(batch).unwrap();
+
+        let calls = calls.lock().unwrap();
+        let methods: Vec<&str> = calls.iter().map(|(method, ..)| method.as_str()).collect();
+        assert_eq!(methods, ["getblockcount", "getblock", "batch"]);
+        assert!(calls[0].1 >= Duration::from_millis(50));
+        assert!(calls[0].2);
+        assert!(!calls[1].2);
+        assert!(calls[2].2);
+    }
+
+    #[test]
+    fn test_migrate_wallet_passphrase_redacted() {
+        let server = MockServer::start(|req| match req.params()[1].as_str() {
+            Some("hunter2") => req.result(json!({
+                "wallet_name": "legacy",
+                "watchonly_name": "legacy_watchonly",
+                "backup_path": "/home/bitcoin/.bitcoin/legacy/legacy-1700000000.legacy.bak",
+            })),
+            _ => req.error(
+                -4,
+                "Error: Wallet decryption failed, the wallet passphrase was incorrect.",
+            ),
+        });
+        let output: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let hook_output = output.clone();
+        let rpc = Client::builder(server.url(), "username", "password")
+            .on_request(move |method, duration, res| {
+                hook_output
+                    .lock()
+                    .unwrap()
+                    .push(format!("{method} {duration:?} {res:?}"));
+            })
+            .build();
+        let passphrase = Secret::from("hunter2");
+
+        let migrate = || {
+            let res = rpc
+                .migrate_wallet(Some("legacy"), Some(&passphrase))
+                .unwrap();
+            rpc.migrate_wallet(Some("legacy"), Some(&Secret::from("hunter3")))
+                .unwrap_err();
+            res
+        };
+        #[cfg(feature = "tracing")]
+        let res = tracing::subscriber::with_default(EventRecorder(output.clone()), migrate);
+        #[cfg(not(feature = "tracing"))]
+        let res = migrate();
+
+        assert_eq!(server.requests()[0].params(), &json!(["legacy", "hunter2"]));
+        assert_eq!(res.wallet_name, "legacy");
+        assert_eq!(res.watchonly_name.as_deref(), Some("legacy_watchonly"));
+        assert_eq!(res.solvables_name, None);
+
+        let output = output.lock().unwrap();
+        assert_eq!(output.len(), if cfg!(feature = "tracing") { 4 } else { 2 });
+        for line in output.iter() {
+            assert!(!line.contains("hunter"), "{line}");
+        }
+        assert!(!format!("{:?}", Some(&passphrase)).contains("hunter"));
+    }
+
+    /// Record the fields of the `tracing` events
+    #[cfg(feature = "tracing")]
+    struct EventRecorder(Arc<Mutex<Vec<String>>>);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for EventRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct Visitor(String);
+
+            impl tracing::field::Visit for Visitor {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    self.0.push_str(&format!("{}={value:?} ", field.name()));
+                }
+            }
+
+            let mut visitor = Visitor(String::new());
+            event.record(&mut visitor);
+            self.0.lock().unwrap().push(visitor.0);
+        }
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[test]
+    fn test_sync_progress() {
+        let mock = |blocks: u64, headers: u64, progress: f64| {
+            let mut info = fixture("getblockchaininfo.json");
+            info["blocks"] = json!(blocks);
+            info["headers"] = json!(headers);
+            info["verificationprogress"] = json!(progress);
+            info["initialblockdownload"] = json!(blocks < headers);
+            MockServer::with_result(info)
+        };
+
+        // Mid-sync
+        let server = mock(420_000, 850_000, 0.3412);
+        let rpc = client(&server);
+        assert_eq!(rpc.sync_progress().unwrap(), 0.3412);
+        assert!(!rpc.headers_synced().unwrap());
+        let tip = rpc.get_tip().unwrap();
+        assert_eq!((tip.height, tip.header_height), (420_000, 850_000));
+
+        // Synced, with the progress estimate slightly off
+        let server = mock(850_000, 850_000, 1.000_000_2);
+        let rpc = client(&server);
+        assert_eq!(rpc.sync_progress().unwrap(), 1.0);
+        assert!(rpc.headers_synced().unwrap());
+    }
+
+    #[test]
+    fn test_cookie_auth() {
+        let path = temp_file("client.cookie", "__cookie__:a1b2c3\n");
+        let server = MockServer::with_result(json!(850_000));
+        let rpc = Client::new_with_cookie(server.url(), &path).unwrap();
+
+        rpc.get_block_count().unwrap();
+        // base64("__cookie__:a1b2c3")
+        assert_eq!(
+            server.last_request().authorization.as_deref(),
+            Some("Basic X19jb29raWVfXzphMWIyYzM=")
+        );
+
+        // The cookie is cached
+        fs::write(&path, "__cookie__:d4e5f6").unwrap();
+        rpc.get_block_count().unwrap();
+        assert_eq!(
+            server.last_request().authorization.as_deref(),
+            Some("Basic X19jb29raWVfXzphMWIyYzM=")
+        );
+
+        let path = temp_file("invalid-client.cookie", "a1b2c3");
+        assert!(matches!(
+            Client::new_with_cookie(server.url(), &path),
+            Err(Error::InvalidCookie)
+        ));
+    }
+
+    #[test]
+    fn test_auth() {
+        let server = MockServer::with_result(json!(850_000));
+
+        let rpc = Client::with_auth(server.url(), Auth::None);
+        rpc.get_block_count().unwrap();
+        assert_eq!(server.last_request().authorization, None);
+
+        let auth = Auth::UserPass {
+            username: String::from("username"),
+            password: String::from("password"),
+        };
+        let rpc = Client::with_auth(server.url(), auth);
+        rpc.get_block_count().unwrap();
+        // base64("username:password")
+        assert_eq!(
+            server.last_request().authorization.as_deref(),
+            Some("Basic dXNlcm5hbWU6cGFzc3dvcmQ=")
+        );
+
+        // The password isn't printed
+        let debug: String = format!("{rpc:?}");
+        assert!(debug.contains(r#"UserPass { username: "username", password: "***" }"#));
+    }
+
+    #[test]
+    fn test_get_block_info() {
+        let server = MockServer::with_result(fixture("getblock_verbosity1.json"));
+        let rpc = client(&server);
+        let genesis = genesis_block(Network::Bitcoin);
+
+        let info = rpc.get_block_info(&genesis.block_hash()).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([genesis.block_hash(), 1])
+        );
+        assert_eq!(info.hash, genesis.block_hash());
+        assert_eq!(info.height, 0);
+        assert_eq!(info.confirmations, 850_000);
+        assert_eq!(info.merkle_root, genesis.header.merkle_root);
+        assert_eq!(info.time, genesis.header.time);
+        assert_eq!(info.nonce, genesis.header.nonce);
+        assert_eq!(info.bits, "1d00ffff");
+        assert_eq!(info.tx, vec![genesis.txdata[0].compute_txid()]);
+        assert_eq!(info.weight, genesis.weight().to_wu());
+        // No previous block for the genesis
+        assert_eq!(info.previous_block_hash, None);
+        assert!(info.next_block_hash.is_some());
+    }
+
+    /// Mock a chain where `getblockhash` returns `hash(n)` at the `n`-th call
+    fn mock_reorg<F>(hash: F) -> MockServer
+    where
+        F: Fn(usize) -> String + Send + Sync + 'static,
+    {
+        let calls = AtomicUsize::new(0);
+        MockServer::start(move |req| match req.method() {
+            "getblockhash" => req.result(json!(hash(calls.fetch_add(1, Ordering::SeqCst)))),
+            _ => {
+                // Tell the blocks apart by the nonce: the height of `hash_at`
+                let hash: &str = req.params()[0].as_str().unwrap();
+                let mut block = fixture("getblock_verbosity2.json");
+                block["hash"] = json!(hash);
+                block["nonce"] = json!(u32::from_str_radix(&hash[56..], 16).unwrap());
+                req.result(block)
+            }
+        })
+    }
+
+    #[test]
+    fn test_get_block_at_height_consistent() {
+        // Reorg between the fetch and the check
+        let server = mock_reorg(|n| if n == 0 { hash_at(1) } else { hash_at(2) });
+        let block = client(&server).get_block_at_height_consistent(1).unwrap();
+        let methods: Vec<String> = server
+            .requests()
+            .iter()
+            .map(|req| req.method().to_string())
+            .collect();
+        assert_eq!(
+            methods,
+            [
+                "getblockhash",
+                "getblock",
+                "getblockhash",
+                "getblockhash",
+                "getblock",
+                "getblockhash"
+            ]
+        );
+        assert_eq!(server.last_request().params(), &json!([1]));
+        // The block of the new chain
+        assert_eq!(block.header.nonce, 2);
+
+        // The chain keeps changing
+        let server = mock_reorg(|n| hash_at(n as u64));
+        assert!(matches!(
+            client(&server).get_block_at_height_consistent(1),
+            Err(Error::Reorg)
+        ));
+        assert_eq!(server.requests().len(), 9);
+    }
+
+    #[test]
+    fn test_cookie_reload() {
+        // base64("__cookie__:new")
+        const NEW: &str = "Basic X19jb29raWVfXzpuZXc=";
+        let path = temp_file("reload.cookie", "__cookie__:old");
+        let server = MockServer::start(|req| match req.authorization.as_deref() {
+            Some(NEW) => req.result(json!(850_000)),
+            _ => MockResponse::raw(String::new()).status(401),
+        });
+        let rpc = Client::new_with_cookie(server.url(), &path).unwrap();
+        assert!(matches!(rpc.get_block_count(), Err(Error::Unauthorized)));
+        // Sent again after reading the same cookie
+        assert_eq!(server.requests().len(), 2);
+
+        // Node restarted
+        fs::write(&path, "__cookie__:new").unwrap();
+        assert_eq!(rpc.get_block_count().unwrap(), 850_000);
+        assert_eq!(server.requests().len(), 4);
+        assert_eq!(server.last_request().authorization.as_deref(), Some(NEW));
+
+        // Not sent again with a password
+        let server = MockServer::start(|_| MockResponse::raw(String::new()).status(401));
+        assert!(matches!(
+            client(&server).get_block_count(),
+            Err(Error::Unauthorized)
+        ));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_retry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let server_calls = calls.clone();
+        let server =
+            MockServer::start(
+                move |req| match server_calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => req.error(-28, "Loading block index..."),
+                    1 | 2 => MockResponse::raw(String::new()).status(503),
+                    _ => req.result(json!(850_000)),
+                },
+            );
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(20),
+            max_delay: Duration::from_millis(40),
+            ..Default::default()
+        };
+        let rpc = client(&server).with_retry(Some(policy));
+
+        let start = Instant::now();
+        assert_eq!(rpc.get_block_count().unwrap(), 850_000);
+        assert_eq!(server.requests().len(), 4);
+        // At least half of each backoff: 20ms, 40ms and 40ms (capped)
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        // Attempts exhausted
+        let server = MockServer::start(|_| MockResponse::raw(String::new()).status(503));
+        let rpc = client(&server).with_retry(Some(RetryPolicy {
+            max_attempts: 3,
+            ..policy
+        }));
+        assert!(matches!(
+            rpc.get_block_count(),
+            Err(Error::ServiceUnavailable)
+        ));
+        assert_eq!(server.requests().len(), 3);
+
+        // Not transient
+        let server = MockServer::start(|req| req.error(-8, "Block height out of range"));
+        let rpc = client(&server).with_retry(Some(policy));
+        assert!(rpc.get_block_hash(1_000_000).is_err());
+        assert_eq!(server.requests().len(), 1);
+
+        // Batches: all the calls failing while warming up, then a 503
+        let calls = Arc::new(AtomicUsize::new(0));
+        let server_calls = calls.clone();
+        let server =
+            MockServer::start(
+                move |req| match server_calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => req.batch(|_, _| Err((-28, "Loading block index..."))),
+                    1 => MockResponse::raw(String::new()).status(503),
+                    _ => req.batch(|_, params| match params[0].as_u64() {
+                        Some(1) => Ok(json!(hash_at(1))),
+                        _ => Err((-8, "Block height out of range")),
+                    }),
+                },
+            );
+        let rpc = client(&server).with_retry(Some(policy));
+        let mut batch = Batch::new();
+        let found = batch.get_block_hash(1);
+        let missing = batch.get_block_hash(1_000_000);
+        let mut res = rpc.send_batch(batch).unwrap();
+        // Not retried after the third attempt, since only one call failed
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(res.take(found).unwrap().to_string(), hash_at(1));
+        assert!(matches!(
+            res.take(missing),
+            Err(Error::NotFoundRpc { code: -8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_labels() {
+        let server = MockServer::start(|req| {
+            match req.method() {
+            "getaddressesbylabel" => req.result(json!({
+                "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080": { "purpose": "receive" },
+                "bcrt1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqc8gma6": { "purpose": "receive" },
+                "2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc": { "purpose": "send" },
+            })),
+            _ => req.result(json!(["", "deposits"])),
+        }
+        });
+        let rpc = client(&server);
+
+        let addresses = rpc.get_addresses_by_label("deposits").unwrap();
+        assert_eq!(server.last_request().params(), &json!(["deposits"]));
+        assert_eq!(addresses.len(), 3);
+        for (address, purpose) in addresses {
+            let address = address.require_network(Network::Regtest).unwrap();
+            let expected = if address.to_string().starts_with("bcrt1") {
+                "receive"
+            } else {
+                "send"
+            };
+            assert_eq!(purpose.purpose, expected);
+        }
+
+        let labels = rpc.list_labels(Some(LabelPurpose::Receive)).unwrap();
+        assert_eq!(labels, vec!["", "deposits"]);
+        assert_eq!(server.last_request().params(), &json!(["receive"]));
+    }
+
+    #[test]
+    fn test_set_label() {
+        let server = MockServer::start(|req| match req.method() {
+            "getnewaddress" => req.result(json!("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")),
+            _ => req.result(Value::Null),
+        });
+        let rpc = client(&server).wallet("hot");
+
+        let address = rpc
+            .get_new_address_with_label("deposits", Some(AddressType::Bech32))
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!(["deposits", "bech32"])
+        );
+        let address = address.require_network(Network::Regtest).unwrap();
+
+        rpc.set_label(&address, "rent").unwrap();
+        assert_eq!(server.last_request().method(), "setlabel");
+        assert_eq!(server.last_request().params(), &json!([address, "rent"]));
+    }
+
+    #[test]
+    fn test_get_block_verbosity() {
+        let server = MockServer::start(|req| {
+            let verbosity = req.params()[1].as_u64().unwrap();
+            req.result(fixture(&format!("getblock_verbosity{verbosity}.json")))
+        });
+        let rpc = client(&server);
+        let genesis = genesis_block(Network::Bitcoin);
+        let hash = genesis.block_hash();
+
+        for verbosity in 0..=3 {
+            let res = rpc.get_block_verbosity(&hash, verbosity).unwrap();
+            assert_eq!(server.last_request().params(), &json!([hash, verbosity]));
+            match (verbosity, res) {
+                (0, GetBlockResponse::Hex(hex)) => {
+                    assert_eq!(hex, encode::serialize_hex(&genesis))
+                }
+                (1, GetBlockResponse::Verbose(block)) => {
+                    assert_eq!(block.hash, hash);
+                    assert!(matches!(
+                        block.tx.as_slice(),
+                        [VerboseBlockTx::Txid(txid)] if *txid == genesis.txdata[0].compute_txid()
+                    ));
+                }
+                (2, GetBlockResponse::Decoded(block)) => assert_eq!(block, genesis),
+                (3, GetBlockResponse::VerboseWithPrevouts(block)) => {
+                    assert_eq!(block.n_tx, 2);
+                    let txs: Vec<&VerboseBlockTransaction> = block
+                        .tx
+                        .iter()
+                        .map(|tx| match tx {
+                            VerboseBlockTx::Transaction(tx) => tx.as_ref(),
+                            VerboseBlockTx::Txid(..) => panic!("expected a transaction"),
+                        })
+                        .collect();
+                    // No prevout for the coinbase input
+                    assert!(txs[0].vin[0].prevout.is_none());
+                    assert_eq!(txs[0].fee, None);
+                    let prevout = txs[1].vin[0].prevout.as_ref().unwrap();
+                    assert_eq!(prevout.value, Amount::from_btc(1.0).unwrap());
+                    assert_eq!(prevout.height, 101);
+                    assert_eq!(txs[1].fee, Some(Amount::from_sat(10_000)));
+                    assert_eq!(txs[1].transaction.compute_txid(), txs[1].txid);
+                }
+                (verbosity, res) => panic!("unexpected response for {verbosity}: {res:?}"),
+            }
+        }
+
+        // Rejected without calling the node
+        let requests: usize = server.requests().len();
+        assert!(matches!(
+            rpc.get_block_verbosity(&hash, 4),
+            Err(Error::InvalidVerbosity(4))
+        ));
+        assert_eq!(server.requests().len(), requests);
+    }
+
+    #[test]
+    fn test_import_multi() {
+        let server = MockServer::with_result(json!([
+            { "success": true },
+            {
+                "success": false,
+                "warnings": ["Some private keys are missing"],
+                "error": { "code": -5, "message": "Invalid P2SH redeemScript" },
+            },
+        ]));
+        let rpc = client(&server).wallet("watch");
+
+        let address = Address::from_str("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")
+            .unwrap()
+            .require_network(Network::Regtest)
+            .unwrap();
+        let mut watch_only = ImportMultiRequest::new(
+            ImportMultiScriptPubKey::Address(address),
+            ImportMultiTimestamp::Now,
+        );
+        watch_only.watch_only = Some(true);
+        watch_only.label = Some("cold".to_string());
+
+        let redeem_script = ScriptBuf::from_hex("51").unwrap();
+        let p2sh = ImportMultiRequest {
+            redeem_script: Some(redeem_script.clone()),
+            ..ImportMultiRequest::new(
+                ImportMultiScriptPubKey::Script(redeem_script.to_p2sh()),
+                ImportMultiTimestamp::Time(1_700_000_000),
+            )
+        };
+
+        let options = ImportMultiOptions {
+            rescan: Some(false),
+        };
+        let res = rpc
+            .import_multi(&[watch_only, p2sh], Some(&options))
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([
+                [
+                    {
+                        "scriptPubKey": { "address": "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080" },
+                        "timestamp": "now",
+                        "watchonly": true,
+                        "label": "cold",
+                    },
+                    {
+                        "scriptPubKey": "a914da1745e9b549bd0bfa1a569971c77eba30cd5a4b87",
+                        "timestamp": 1_700_000_000,
+                        "redeemscript": "51",
+                    },
+                ],
+                { "rescan": false },
+            ])
+        );
+        assert!(res[0].success);
+        assert!(res[0].error.is_none());
+        assert!(!res[1].success);
+        assert_eq!(res[1].warnings.len(), 1);
+        assert_eq!(res[1].error.as_ref().unwrap().code, -5);
+    }
+
+    #[test]
+    fn test_list_since_block_without_removed() {
+        // Core omits `removed` if `include_removed` is false
+        let server = MockServer::start(|req| {
+            let mut res = fixture("listsinceblock_reorg.json");
+            if req.params()[3] == json!(false) {
+                res.as_object_mut().unwrap().remove("removed");
+            }
+            req.result(res)
+        });
+        let rpc = client(&server).wallet("hot");
+
+        let res = rpc.list_since_block(None, None, None, Some(false)).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([null, null, null, false])
+        );
+        assert_eq!(res.transactions.len(), 1);
+        assert!(res.removed.is_empty());
+
+        let res = rpc.list_since_block(None, None, None, Some(true)).unwrap();
+        assert_eq!(res.removed.len(), 1);
+    }
+
+    #[test]
+    fn test_get_zmq_notifications() {
+        let server = MockServer::with_result(fixture("getzmqnotifications.json"));
+        let notifications = client(&server).get_zmq_notifications().unwrap();
+        assert_eq!(server.last_request().method(), "getzmqnotifications");
+        assert_eq!(notifications.len(), 3);
+        assert_eq!(notifications[0].notification_type, "pubhashblock");
+        assert_eq!(notifications[0].address, "tcp://127.0.0.1:28332");
+        assert_eq!(notifications[1].notification_type, "pubrawtx");
+        assert_eq!(notifications[1].hwm, 5000);
+        assert_eq!(notifications[2].address, "ipc:///tmp/bitcoind.sock");
+    }
+
+    #[test]
+    fn test_get_block_template_long_poll() {
+        let server = MockServer::with_result(fixture("getblocktemplate.json"));
+        let rpc = client(&server);
+
+        let template = rpc.get_block_template(&["segwit"], None).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([{ "rules": ["segwit"] }])
+        );
+        assert_eq!(template.height, 102);
+        assert_eq!(template.transactions.len(), 1);
+        assert_eq!(template.transactions[0].fee, Amount::from_sat(10_000));
+        assert_eq!(
+            template.transactions[0].transaction.compute_txid(),
+            template.transactions[0].txid
+        );
+        assert_eq!(template.coinbase_value, Amount::from_sat(5_000_010_000));
+
+        // The id is sent back as is
+        rpc.get_block_template(&["segwit"], Some(&template.long_poll_id))
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([{ "rules": ["segwit"], "longpollid": template.long_poll_id }])
+        );
+    }
+
+    #[test]
+    fn test_blocks_to_confirm() {
+        let curve: Value = fixture("estimatesmartfee_curve.json");
+        let server = MockServer::start(move |req| {
+            req.batch(|_, params| {
+                let target = params[0].as_u64().unwrap() as usize;
+                Ok(curve[target - 1].clone())
+            })
+        });
+        let rpc = client(&server);
+
+        let blocks = |sat_vb: u64, max_target: u16| {
+            rpc.blocks_to_confirm(FeeRate::from_sat_per_vb(sat_vb).unwrap(), max_target)
+                .unwrap()
+        };
+        // Target 1 is estimated as 2
+        assert_eq!(blocks(100, 12), Some(2));
+        assert_eq!(blocks(52, 12), Some(2));
+        assert_eq!(blocks(51, 12), Some(3));
+        assert_eq!(blocks(30, 12), Some(5));
+        assert_eq!(blocks(10, 12), Some(10));
+        assert_eq!(blocks(10, 9), None);
+        assert_eq!(blocks(1, 12), None);
+
+        // A single batch with all the targets
+        let requests = server.requests();
+        assert_eq!(requests.len(), 7);
+        assert_eq!(requests[0].body.as_array().unwrap().len(), 12);
+        assert_eq!(requests[0].body[11]["params"], json!([12]));
+    }
+
+    #[test]
+    fn test_get_raw_transaction_in_block() {
+        let genesis = genesis_block(Network::Bitcoin);
+        let tx = genesis.txdata[0].clone();
+        let server = MockServer::with_result(json!(encode::serialize_hex(&tx)));
+        let rpc = client(&server);
+
+        let txid = tx.compute_txid();
+        let hash = genesis.block_hash();
+        assert_eq!(rpc.get_raw_transaction_in_block(&txid, &hash).unwrap(), tx);
+        assert_eq!(server.last_request().params(), &json!([txid, false, hash]));
+    }
+
+    #[test]
+    fn test_connection_reuse() {
+        let server = MockServer::with_result(json!(850_000));
+        let rpc = Client::builder(server.url(), "username", "password")
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(30))
+            .build();
+
+        for _ in 0..10 {
+            rpc.get_block_count().unwrap();
+        }
+        assert_eq!(server.connections(), 1);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let rpc = rpc.clone();
+                thread::spawn(move || {
+                    for _ in 0..25 {
+                        assert_eq!(rpc.get_block_count().unwrap(), 850_000);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(server.requests().len(), 110);
+        // At most one connection for each concurrent thread
+        assert!(server.connections() <= 4);
+    }
+
+    #[test]
+    fn test_migrate_wallet() {
+        let server = MockServer::with_result(json!({
+            "wallet_name": "legacy",
+            "watchonly_name": "legacy_watchonly",
+            "backup_path": "/home/bitcoin/.bitcoin/legacy/legacy-1700000000.legacy.bak",
+        }));
+        let rpc = client(&server).wallet("legacy");
+
+        // The wallet of the endpoint
+        let res = rpc
+            .migrate_wallet(None, Some(&Secret::from("hunter2")))
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!(["legacy", "hunter2"])
+        );
+        assert_eq!(res.wallet_name, "legacy");
+        assert_eq!(res.watchonly_name.as_deref(), Some("legacy_watchonly"));
+        assert_eq!(res.solvables_name, None);
+    }
+
+    #[test]
+    fn test_get_tx_outs() {
+        let server = MockServer::start(|req| {
+            req.batch(|_, params| match params[1].as_u64() {
+                // Unspent
+                Some(0) => Ok(json!({
+                    "bestblock": "00000000000000000002a0b5db2a7f8d9087464c2586b546be7bce8eb53b8187",
+                    "confirmations": 10,
+                    "value": 0.0001,
+                    "scriptPubKey": {
+                        "asm": "0 e8df018c7e326cc253faac7e46cdc51e68542c42",
+                        "desc": "addr(bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq)#kwtnh4ht",
+                        "hex": "0014e8df018c7e326cc253faac7e46cdc51e68542c42",
+                        "address": "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+                        "type": "witness_v0_keyhash",
+                    },
+                    "coinbase": false,
+                })),
+                // Spent or not existing
+                _ => Ok(Value::Null),
+            })
+        });
+
+        let txid =
+            Txid::from_str("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b")
+                .unwrap();
+        let txouts = client(&server)
+            .get_tx_outs(&[(txid, 1), (txid, 0), (txid, 2)], true)
+            .unwrap();
+        assert_eq!(txouts.len(), 3);
+        assert!(txouts[0].is_none());
+        assert_eq!(txouts[1].as_ref().unwrap().value, Amount::from_sat(10_000));
+        assert!(txouts[2].is_none());
+
+        // A single batch request
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].body[1]["method"], "gettxout");
+        assert_eq!(requests[0].body[1]["params"], json!([txid, 0, true]));
+    }
+
+    #[test]
+    fn test_multisig() {
+        let server = MockServer::start(|req| {
+            let mut res = fixture("createmultisig.json");
+            if req.method() == "addmultisigaddress" {
+                res["warnings"] = json!(["Unable to make chosen address type, please ensure no uncompressed public keys are present."]);
+            }
+            req.result(res)
+        });
+        let rpc = client(&server);
+        let keys: Vec<PublicKey> = [
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+        ]
+        .iter()
+        .map(|key| PublicKey::from_str(key).unwrap())
+        .collect();
+
+        let multisig = rpc
+            .create_multisig(2, &keys, Some(AddressType::Bech32))
+            .unwrap();
+        assert_eq!(server.last_request().params(), &json!([2, keys, "bech32"]));
+        let address = multisig.address.require_network(Network::Regtest).unwrap();
+        assert_eq!(
+            address,
+            Address::p2wsh(&multisig.redeem_script, Network::Regtest)
+        );
+        assert!(multisig.descriptor.starts_with("wsh(multi(2,"));
+        assert!(multisig.warnings.is_empty());
+
+        let multisig = rpc
+            .add_multisig_address(2, &keys, Some("vault"), Some(AddressType::Bech32))
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([2, keys, "vault", "bech32"])
+        );
+        assert_eq!(multisig.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_rescan_blockchain() {
+        let server = MockServer::start(|req| match req.method() {
+            "rescanblockchain" => req.result(json!({ "start_height": 100, "stop_height": 200 })),
+            _ => {
+                let mut info = fixture("getwalletinfo.json");
+                info["scanning"] = json!({ "duration": 60, "progress": 0.25 });
+                req.result(info)
+            }
+        });
+        let rpc = client(&server).wallet("hot");
+
+        let res = rpc.rescan_blockchain(Some(100), None).unwrap();
+        assert_eq!(server.last_request().params(), &json!([100]));
+        assert_eq!(res.start_height, 100);
+        assert_eq!(res.stop_height, 200);
+
+        let progress = rpc.get_rescan_progress().unwrap().unwrap();
+        assert_eq!(server.last_request().method(), "getwalletinfo");
+        assert_eq!(progress.duration, 60);
+        assert_eq!(progress.progress, 0.25);
+
+        // Not scanning
+        let server = MockServer::with_result(fixture("getwalletinfo.json"));
+        assert!(client(&server).get_rescan_progress().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_named_params() {
+        let server = MockServer::with_result(json!({ "feerate": 0.0001, "blocks": 6 }));
+        let rpc = client(&server);
+
+        let _: Value = rpc
+            .call(
+                "estimatesmartfee",
+                Params::new()
+                    .named("conf_target", 6)
+                    .named("estimate_mode", "economical"),
+            )
+            .unwrap();
+        let body = server.last_request().body;
+        assert_eq!(body["jsonrpc"], "2.0");
+        assert_eq!(body["method"], "estimatesmartfee");
+        assert_eq!(
+            body["params"],
+            json!({ "conf_target": 6, "estimate_mode": "economical" })
+        );
+    }
+
+    #[test]
+    fn test_optional_params_trimmed() {
+        let server = MockServer::with_result(json!([]));
+        let rpc = client(&server).wallet("hot");
+
+        rpc.list_unspent(None, None, None, None, None).unwrap();
+        assert_eq!(server.last_request().params(), &json!([]));
+
+        rpc.list_unspent(Some(1), None, None, None, None).unwrap();
+        assert_eq!(server.last_request().params(), &json!([1]));
+
+        // Omitted params followed by other params are still sent as `null`
+        rpc.list_unspent(None, None, None, Some(false), None)
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([null, null, null, false])
+        );
+    }
+
+    /// Mock a block at `height`, with the coinbase of the regtest genesis paying `value`
+    fn mock_block(height: u64, value: Amount) -> MockServer {
+        let mut block: Block = genesis_block(Network::Regtest);
+        block.txdata[0].output[0].value = value;
+        let hex: String = encode::serialize_hex(&block);
+        MockServer::start(move |req| {
+            req.batch(|method, _| match method {
+                "getblockheader" => Ok(json!({ "height": height })),
+                "getblock" => Ok(json!(hex)),
+                _ => Err((-32601, "Method not found")),
+            })
+        })
+    }
+
+    #[test]
+    fn test_get_block_subsidy_and_fees() {
+        let hash = genesis_block(Network::Regtest).block_hash();
+
+        let server = mock_block(0, Amount::from_btc(50.0).unwrap());
+        let reward = client(&server)
+            .get_block_subsidy_and_fees(&hash, Network::Regtest)
+            .unwrap();
+        assert_eq!(reward.subsidy, Amount::from_btc(50.0).unwrap());
+        assert_eq!(reward.fees, Amount::ZERO);
+        // Header and block in a single batch
+        assert_eq!(server.requests().len(), 1);
+
+        // First regtest halving
+        let server = mock_block(150, Amount::from_sat(2_500_012_345));
+        let reward = client(&server)
+            .get_block_subsidy_and_fees(&hash, Network::Regtest)
+            .unwrap();
+        assert_eq!(reward.subsidy, Amount::from_btc(25.0).unwrap());
+        assert_eq!(reward.fees, Amount::from_sat(12_345));
+
+        let server = mock_block(149, Amount::from_btc(25.0).unwrap());
+        assert!(matches!(
+            client(&server).get_block_subsidy_and_fees(&hash, Network::Regtest),
+            Err(Error::CoinbaseBelowSubsidy)
+        ));
+    }
+
+    #[test]
+    fn test_get_coinbase() {
+        let block: Block = genesis_block(Network::Regtest);
+        let server = MockServer::with_result(json!(encode::serialize_hex(&block)));
+        let coinbase = client(&server).get_coinbase(&block.block_hash()).unwrap();
+        assert!(coinbase.is_coinbase());
+        assert_eq!(coinbase.compute_txid(), block.txdata[0].compute_txid());
+    }
+
+    #[test]
+    fn test_get_new_address() {
+        let cases = [
+            (
+                "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+                Network::Bitcoin,
+                AddressType::Bech32,
+            ),
+            (
+                "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+                Network::Testnet,
+                AddressType::Bech32,
+            ),
+            (
+                "bcrt1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqc8gma6",
+                Network::Regtest,
+                AddressType::Bech32m,
+            ),
+            (
+                "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47zagq",
+                Network::Signet,
+                AddressType::Bech32m,
+            ),
+        ];
+
+        for (address, network, address_type) in cases {
+            let server = MockServer::with_result(json!(address));
+            let rpc = client(&server);
+
+            let res = rpc
+                .get_new_address(Some("label"), Some(address_type))
+                .unwrap();
+            assert_eq!(
+                server.last_request().params(),
+                &json!(["label", address_type])
+            );
+            let checked = res.require_network(network).unwrap();
+            assert_eq!(checked.to_string(), address);
+
+            let res = rpc.get_raw_change_address(Some(address_type)).unwrap();
+            assert!(res.is_valid_for_network(network));
+            assert_eq!(server.last_request().method(), "getrawchangeaddress");
+        }
+    }
+
+    #[test]
+    fn test_send_many() {
+        let txid = Txid::from_str(&"c3".repeat(32)).unwrap();
+        let server = MockServer::with_result(json!(txid));
+        let rpc = client(&server).wallet("hot");
+
+        let address = Address::from_str("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080").unwrap();
+        let amounts = HashMap::from([(address, Amount::from_sat(100_000))]);
+        let res = rpc.send_many(&amounts, None).unwrap();
+        assert_eq!(res.txid, txid);
+        assert_eq!(res.fee_reason, None);
+        assert_eq!(
+            server.last_request().params(),
+            &json!(["", { "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080": 0.001 }])
+        );
+    }
+
+    #[test]
+    fn test_get_block_stats() {
+        let server = MockServer::start(|req| {
+            let mut stats = fixture("getblockstats.json");
+            if let Some(fields) = req.params()[1].as_array() {
+                // Only the requested stats
+                let stats = stats.as_object_mut().unwrap();
+                stats.retain(|name, _| fields.contains(&json!(name)));
+            }
+            req.result(stats)
+        });
+        let rpc = client(&server);
+
+        let stats = rpc
+            .get_block_stats(BlockStatsTarget::Height(850_000), None)
+            .unwrap();
+        assert_eq!(server.last_request().params(), &json!([850_000]));
+        assert_eq!(stats.feerate_percentiles, Some([8, 11, 15, 24, 40]));
+        assert_eq!(stats.txs, Some(3174));
+        assert_eq!(stats.subsidy, Some(312_500_000));
+
+        let hash = stats.block_hash.unwrap();
+        let percentiles = rpc
+            .get_feerate_percentiles(BlockStatsTarget::Hash(hash))
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([hash, ["feerate_percentiles"]])
+        );
+        assert_eq!(percentiles, [8, 11, 15, 24, 40]);
+    }
+
+    #[test]
+    fn test_send_to_address() {
+        let txid = Txid::from_str(&"c3".repeat(32)).unwrap();
+        let server = MockServer::start(move |req| match req.params()[10].as_bool() {
+            Some(true) => req.result(json!({ "txid": txid, "fee_reason": "Fallback fee" })),
+            _ => req.result(json!(txid)),
+        });
+        let rpc = client(&server).wallet("hot");
+        let address = Address::from_str("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")
+            .unwrap()
+            .assume_checked();
+        let amount = Amount::from_sat(150_000);
+
+        let res = rpc.send_to_address(&address, amount, None).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!(["bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080", 0.0015])
+        );
+        assert_eq!(res.txid, txid);
+        assert_eq!(res.fee_reason, None);
+
+        let options = SendToAddressOptions {
+            comment: Some("rent".to_string()),
+            subtract_fee_from_amount: Some(true),
+            replaceable: Some(true),
+            estimate_mode: Some(EstimateMode::Unset),
+            fee_rate: Some(FeeRate::from_sat_per_vb_u32(3)),
+            verbose: Some(true),
+            ..Default::default()
+        };
+        let res = rpc
+            .send_to_address(&address, amount, Some(&options))
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([
+                "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+                0.0015,
+                "rent",
+                null,
+                true,
+                true,
+                null,
+                "UNSET",
+                null,
+                3.0,
+                true,
+            ])
+        );
+        assert_eq!(res.txid, txid);
+        assert_eq!(res.fee_reason.as_deref(), Some("Fallback fee"));
+    }
+
+    #[test]
+    fn test_not_found() {
+        let server = MockServer::start(|req| {
+            match req.method() {
+            "getblockhash" => req.error(-8, "Block height out of range"),
+            "getrawtransaction" => req.error(
+                -5,
+                "No such mempool or blockchain transaction. Use gettransaction for wallet transactions.",
+            ),
+            "getmempoolentry" => req.error(-5, "Transaction not in mempool"),
+            "getblockcount" => req.error(-8, "Unexpected"),
+            // Batch request
+            "" => req.batch(|_, _| Err((-5, "Block not found"))),
+            _ => req.error(-5, "Block not found"),
+        }
+        });
+        let rpc = client(&server);
+        let hash = genesis_block(Network::Bitcoin).block_hash();
+        let txid = Txid::from_str(&"d4".repeat(32)).unwrap();
+
+        let is_not_found = |res: Result<_, Error>, expected: i32| matches!(res, Err(Error::NotFoundRpc { code, .. }) if code == expected);
+        assert!(is_not_found(rpc.get_block_hash(1_000_000).map(drop), -8));
+        assert!(is_not_found(rpc.get_block(&hash).map(drop), -5));
+        assert!(is_not_found(rpc.get_block_info(&hash).map(drop), -5));
+        assert!(is_not_found(
+            rpc.get_block_stats(BlockStatsTarget::Hash(hash), None)
+                .map(drop),
+            -5
+        ));
+        assert!(is_not_found(rpc.get_raw_transaction(&txid).map(drop), -5));
+        assert!(is_not_found(
+            rpc.call::<Value>("getblockheader", Params::new().push(hash))
+                .map(drop),
+            -5
+        ));
+        assert!(is_not_found(
+            rpc.call::<Value>("getmempoolentry", Params::new().push(txid))
+                .map(drop),
+            -5
+        ));
+
+        // Batch calls
+        assert!(is_not_found(
+            rpc.get_block_subsidy_and_fees(&hash, Network::Bitcoin)
+                .map(drop),
+            -5
+        ));
+
+        // Same code, but not a lookup
+        assert!(matches!(
+            rpc.get_block_count(),
+            Err(Error::Rpc { code: -8, .. })
+        ));
+    }
+
+    #[test]
+    fn test_set_hd_seed() {
+        let server = MockServer::with_result(Value::Null);
+        let rpc = client(&server).wallet("legacy");
+
+        rpc.set_hd_seed(None, None).unwrap();
+        assert_eq!(server.last_request().params(), &json!([]));
+
+        // Omitted params followed by other params are still sent as `null`
+        let seed = PrivateKey::from_slice(&[1; 32], Network::Regtest).unwrap();
+        rpc.set_hd_seed(None, Some(&seed)).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([null, seed.to_wif()])
+        );
+    }
+
+    #[test]
+    fn test_connect_timeout() {
+        // Non-routable: the connection hangs until the connect timeout
+        let rpc = Client::builder("http://10.255.255.1:8332", "username", "password")
+            .connect_timeout(Duration::from_millis(200))
+            .build();
+        let start = Instant::now();
+        // Sandboxed networks may also reset the connection: either way, the request
+        // must not hang
+        assert!(matches!(rpc.get_block_count(), Err(Error::Reqwest(..))));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_set_tx_fee() {
+        let server = MockServer::with_result(json!(true));
+        let rpc = client(&server).wallet("hot");
+
+        assert!(rpc.set_tx_fee(Amount::from_sat(1_000)).unwrap());
+        assert_eq!(server.last_request().params(), &json!([0.00001]));
+
+        rpc.set_tx_fee(Amount::from_sat(123_456)).unwrap();
+        assert_eq!(server.last_request().params(), &json!([0.00123456]));
+
+        rpc.set_tx_fee_rate(FeeRate::from_sat_per_vb_u32(2))
+            .unwrap();
+        assert_eq!(server.last_request().params(), &json!([0.00002]));
+
+        rpc.set_tx_fee_rate(FeeRate::ZERO).unwrap();
+        assert_eq!(server.last_request().params(), &json!([0.0]));
+    }
+
+    #[test]
+    fn test_propose_block() {
+        let server = MockServer::start(|req| {
+            if req.params()[0]["data"]
+                == json!(encode::serialize_hex(&genesis_block(Network::Regtest)))
+            {
+                req.result(Value::Null)
+            } else {
+                req.result(json!("bad-txnmrklroot"))
+            }
+        });
+        let rpc = client(&server);
+
+        let block = genesis_block(Network::Regtest);
+        rpc.propose_block(&block, &["segwit"]).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([{
+                "mode": "proposal",
+                "data": encode::serialize_hex(&block),
+                "rules": ["segwit"],
+            }])
+        );
+
+        let mut block = block;
+        block.header.merkle_root = TxMerkleNode::all_zeros();
+        assert!(matches!(
+            rpc.propose_block(&block, &["segwit"]),
+            Err(Error::BlockProposalRejected(reason)) if reason == "bad-txnmrklroot"
+        ));
+    }
+
+    #[test]
+    fn test_sign_raw_transaction_with_wallet() {
+        let res = fixture("signrawtransactionwithwallet.json");
+        let tx: Transaction = encode::deserialize_hex(res["hex"].as_str().unwrap()).unwrap();
+        let server = MockServer::with_result(res);
+        let rpc = client(&server).wallet("hot");
+
+        let prevout = PrevTx {
+            txid: tx.input[0].previous_output.txid,
+            vout: 0,
+            script_pub_key: tx.output[0].script_pubkey.clone(),
+            redeem_script: None,
+            witness_script: None,
+            amount: Some(Amount::ONE_BTC),
+        };
+        let signed = rpc
+            .sign_raw_transaction_with_wallet(
+                &tx,
+                Some(&[prevout]),
+                Some(SighashType::AllPlusAnyoneCanPay),
+            )
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([
+                encode::serialize_hex(&tx),
+                [{
+                    "txid": tx.input[0].previous_output.txid,
+                    "vout": 0,
+                    "scriptPubKey": "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+                    "amount": 1.0,
+                }],
+                "ALL|ANYONECANPAY",
+            ])
+        );
+        assert_eq!(signed.transaction, tx);
+        assert!(!signed.complete);
+        assert_eq!(signed.errors.len(), 1);
+        assert_eq!(signed.errors[0].error, "Input not found or already spent");
+        assert!(signed.errors[0].script_sig.is_empty());
+    }
+
+    #[test]
+    fn test_sign_message() {
+        const SIGNATURE: &str =
+            "H5JYZBtqfaP0zMq8TP2N3xKzJ0uEPUbxS5KYR/lJrqXFSpNJ6QaBcyV9tXHhrLFbNUU8CU+6bjtuwlXsqmhCcWM=";
+        let server = MockServer::start(|req| match (req.method(), req.params()[0].as_str()) {
+            ("signmessage", Some("mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r")) => {
+                req.result(json!(SIGNATURE))
+            }
+            ("signmessage", _) => req.error(-3, "Address does not refer to key"),
+            _ => req.result(json!(
+                req.params()[1] == json!(SIGNATURE) && req.params()[2] == json!("hello")
+            )),
+        });
+        let rpc = client(&server).wallet("legacy");
+        let address = Address::from_str("mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r")
+            .unwrap()
+            .require_network(Network::Regtest)
+            .unwrap();
+
+        let signature = rpc.sign_message(&address, "hello").unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!(["mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r", "hello"])
+        );
+        assert_eq!(signature, SIGNATURE);
+
+        assert!(rpc.verify_message(&address, &signature, "hello").unwrap());
+        assert_eq!(
+            server.last_request().params(),
+            &json!(["mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r", SIGNATURE, "hello"])
+        );
+        assert!(!rpc.verify_message(&address, &signature, "hello!").unwrap());
+
+        // Only legacy addresses
+        let address = Address::from_str("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")
+            .unwrap()
+            .assume_checked();
+        assert!(matches!(
+            rpc.sign_message(&address, "hello"),
+            Err(Error::Rpc { code: -3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_require_txindex() {
+        let index = |info: Value| {
+            let server = MockServer::with_result(info);
+            client(&server).require_txindex()
+        };
+
+        // Missing
+        assert!(matches!(
+            index(json!({})),
+            Err(Error::IndexNotReady { name, synced: false, height: None }) if name == "txindex"
+        ));
+        // Syncing
+        assert!(matches!(
+            index(json!({
+                "txindex": { "synced": false, "best_block_height": 420_000 },
+                "coinstatsindex": { "synced": true, "best_block_height": 850_000 },
+            })),
+            Err(Error::IndexNotReady {
+                synced: false,
+                height: Some(420_000),
+                ..
+            })
+        ));
+        // Ready
+        index(json!({ "txindex": { "synced": true, "best_block_height": 850_000 } })).unwrap();
+    }
+
+    #[test]
+    fn test_upgrade_wallet() {
+        let server = MockServer::start(|req| {
+            match req.params().get(0) {
+            Some(Value::Number(..)) => req.result(json!({
+                "wallet_name": "legacy",
+                "previous_version": 169900,
+                "current_version": 169900,
+                "error": "Cannot downgrade wallet from version 169900 to version 60000. Wallet version unchanged.",
+            })),
+            _ => req.result(json!({
+                "wallet_name": "legacy",
+                "previous_version": 159900,
+                "current_version": 169900,
+                "result": "Wallet upgraded successfully from version 159900 to version 169900.",
+            })),
+        }
+        });
+        let rpc = client(&server).wallet("legacy");
+
+        let res = rpc.upgrade_wallet(None).unwrap();
+        assert_eq!(server.last_request().params(), &json!([]));
+        assert_eq!(res.previous_version, 159_900);
+        assert_eq!(res.current_version, 169_900);
+        assert!(res.result.is_some());
+        assert_eq!(res.error, None);
+
+        let res = rpc.upgrade_wallet(Some(60_000)).unwrap();
+        assert_eq!(server.last_request().params(), &json!([60_000]));
+        assert_eq!(res.previous_version, res.current_version);
+        assert_eq!(res.result, None);
+        assert!(res.error.unwrap().starts_with("Cannot downgrade"));
+    }
+
+    #[test]
+    fn test_call_value() {
+        let server = MockServer::start(|req| match req.method() {
+            "getblockchaininfo" => req.result(fixture("getblockchaininfo.json")),
+            _ => req.result(Value::Null),
+        });
+        let rpc = client(&server);
+
+        // Only the result, without the JSON-RPC envelope
+        let info = rpc.call_value("getblockchaininfo", Params::new()).unwrap();
+        assert_eq!(info, fixture("getblockchaininfo.json"));
+        assert_eq!(info["blocks"], 850_000);
+
+        let res = rpc
+            .call_value("setnetworkactive", Params::new().push(true))
+            .unwrap();
+        assert_eq!(res, Value::Null);
+        assert_eq!(server.last_request().params(), &json!([true]));
+    }
+
+    #[test]
+    fn test_min_relay_fee() {
+        let server = MockServer::with_result(fixture("getmempoolinfo.json"));
+        let rpc = client(&server);
+
+        // Default floor: 1 sat/vB
+        let floor = FeeRate::from_sat_per_vb_u32(1);
+        assert_eq!(rpc.get_min_relay_fee().unwrap(), floor);
+        assert_eq!(rpc.get_mempool_min_fee().unwrap(), floor);
+        assert_eq!(floor.to_sat_per_kwu(), 250);
+
+        let info = rpc.get_mempool_info().unwrap();
+        assert_eq!(info.incremental_relay_fee, Some(floor));
+        assert_eq!(info.total_fee, Some(Amount::from_sat(62_547_862)));
+
+        // Sub-sat floor (0.1 sat/vB)
+        let mut res = fixture("getmempoolinfo.json");
+        res["minrelaytxfee"] = json!(0.000001);
+        let server = MockServer::with_result(res);
+        assert_eq!(
+            client(&server).get_min_relay_fee().unwrap(),
+            FeeRate::from_sat_per_kwu(25)
+        );
+    }
+
+    #[test]
+    fn test_max_response_bytes() {
+        let server = MockServer::with_result(json!("ab".repeat(1_000)));
+        let rpc = Client::builder(server.url(), "username", "password")
+            .max_response_bytes(1_024)
+            .build();
+        assert!(matches!(
+            rpc.get_raw_transaction_hex(&Txid::all_zeros()),
+            Err(Error::ResponseTooLarge)
+        ));
+
+        // Under the limit
+        let server = MockServer::with_result(json!("ab".repeat(100)));
+        let rpc = Client::builder(server.url(), "username", "password")
+            .max_response_bytes(1_024)
+            .build();
+        assert_eq!(
+            rpc.get_raw_transaction_hex(&Txid::all_zeros()).unwrap(),
+            "ab".repeat(100)
+        );
+    }
+
+    #[test]
+    fn test_wallet_passphrase() {
+        let server = MockServer::start(|req| match req.params()[0].as_str() {
+            Some("passphrase") => req.result(Value::Null),
+            Some("unencrypted") => req.error(
+                -15,
+                "Error: running with an unencrypted wallet, but walletpassphrase was called.",
+            ),
+            Some("unlocked") => req.error(-17, "Error: Wallet is already unlocked."),
+            _ => req.error(-14, "Error: The wallet passphrase entered was incorrect."),
+        });
+        let rpc = client(&server).wallet("hot");
+
+        rpc.wallet_passphrase(&Secret::from("passphrase"), 60)
+            .unwrap();
+        let req = server.last_request();
+        assert_eq!(req.method(), "walletpassphrase");
+        assert_eq!(req.params(), &json!(["passphrase", 60]));
+
+        assert!(matches!(
+            rpc.wallet_passphrase(&Secret::from("wrong"), 60),
+            Err(Error::WalletPassphraseIncorrect)
+        ));
+        assert!(matches!(
+            rpc.wallet_passphrase(&Secret::from("unencrypted"), 60),
+            Err(Error::WalletNotEncrypted)
+        ));
+        assert!(matches!(
+            rpc.wallet_passphrase(&Secret::from("unlocked"), 60),
+            Err(Error::WalletAlreadyUnlocked)
+        ));
+        assert!(matches!(
+            rpc.wallet_passphrase_change(&Secret::from("wrong"), &Secret::from("new")),
+            Err(Error::WalletPassphraseIncorrect)
+        ));
+    }
+
+    #[test]
+    fn test_raw_block() {
+        let submitted = AtomicUsize::new(0);
+        let server = MockServer::start(move |req| match req.method() {
+            "getblock" => req.result(fixture("getblock_verbosity0.json")),
+            _ if submitted.fetch_add(1, Ordering::SeqCst) == 0 => req.result(Value::Null),
+            _ => req.result(json!("duplicate")),
+        });
+        let rpc = client(&server);
+        let genesis = genesis_block(Network::Bitcoin);
+
+        let raw = rpc.get_block_raw(&genesis.block_hash()).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([genesis.block_hash(), 0])
+        );
+        assert_eq!(raw, encode::serialize(&genesis));
+
+        // The same bytes are sent back
+        rpc.submit_block_raw(&raw).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([fixture("getblock_verbosity0.json")])
+        );
+        assert!(matches!(
+            rpc.submit_block_raw(&raw),
+            Err(Error::BlockRejected(reason)) if reason == "duplicate"
+        ));
+    }
+
+    fn hash_at(height: u64) -> String {
+        format!("{height:064x}")
+    }
+
+    /// Mock a chain of height `tip`, replying `best` to `getbestblockhash`
+    fn mock_chain(tip: u64, best: String) -> MockServer {
+        MockServer::start(move |req| {
+            if req.body.is_array() {
+                return req.batch(|method, params| match method {
+                    "getblockhash" => Ok(json!(hash_at(params[0].as_u64().unwrap()))),
+                    "getbestblockhash" => Ok(json!(best)),
+                    _ => Err((-32601, "Method not found")),
+                });
+            }
+            match req.method() {
+                "getblockcount" => req.result(json!(tip)),
+                _ => req.error(-32601, "Method not found"),
+            }
+        })
+    }
+
+    #[test]
+    fn test_get_block_locator() {
+        let server = mock_chain(100, hash_at(100));
+        let locator = client(&server).get_block_locator().unwrap();
+        let expected: Vec<BlockHash> = locator_heights(100)
+            .into_iter()
+            .map(|height| BlockHash::from_str(&hash_at(height)).unwrap())
+            .collect();
+        assert_eq!(locator, expected);
+        // getblockcount and a single batch
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_get_block_locator_tip_changed() {
+        let server = mock_chain(100, hash_at(101));
+        assert!(matches!(
+            client(&server).get_block_locator(),
+            Err(Error::Reorg)
+        ));
+        assert_eq!(server.requests().len(), 6);
+    }
+
+    #[test]
+    fn test_send_batch() {
+        let server = mock_chain(100, hash_at(100));
+        let rpc = client(&server);
+
+        let mut batch = Batch::new();
+        let found = batch.get_block_hash(1);
+        let best = batch.get_best_block_hash();
+        let unknown = batch.call::<u64>("getblockcount", Params::new());
+        assert_eq!(batch.len(), 3);
+
+        let mut res = rpc.send_batch(batch).unwrap();
+        // Single request
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(res.take(found).unwrap().to_string(), hash_at(1));
+        assert_eq!(res.take(best).unwrap().to_string(), hash_at(100));
+        // A failed call doesn't affect the others
+        assert!(matches!(
+            res.take(unknown),
+            Err(Error::Rpc { code: -32601, .. })
+        ));
+
+        // Nothing sent for an empty batch
+        rpc.send_batch(Batch::new()).unwrap();
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_psbt() {
+        let psbts = fixture("psbt.json");
+        let server = MockServer::start(|req| {
+            let psbts = fixture("psbt.json");
+            match req.method() {
+                "converttopsbt" => req.result(psbts["unsigned"].clone()),
+                "utxoupdatepsbt" => req.result(psbts["updated"].clone()),
+                _ => req.result(psbts["joined"].clone()),
+            }
+        });
+        let rpc = client(&server);
+        let tx: Transaction = encode::deserialize_hex(psbts["tx"].as_str().unwrap()).unwrap();
+
+        let psbt = rpc.convert_to_psbt(&tx, None).unwrap();
+        assert_eq!(server.last_request().params(), &json!([psbts["tx"]]));
+        assert_eq!(psbt, Psbt::from_unsigned_tx(tx.clone()).unwrap());
+
+        let updated = rpc.utxo_update_psbt(&psbt, None).unwrap();
+        assert_eq!(server.last_request().params(), &json!([psbts["unsigned"]]));
+        assert_eq!(updated.unsigned_tx, tx);
+        let utxo = updated.inputs[0].witness_utxo.as_ref().unwrap();
+        assert_eq!(utxo.value, Amount::ONE_BTC);
+        assert_eq!(utxo.script_pubkey, tx.output[0].script_pubkey);
+
+        let other = Psbt::from_str(psbts["other"].as_str().unwrap()).unwrap();
+        let joined = rpc.join_psbts(&[psbt, other.clone()]).unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!([[psbts["unsigned"], psbts["other"]]])
+        );
+        assert_eq!(joined.inputs.len(), 2);
+        assert_eq!(joined.outputs.len(), 2);
+        assert_eq!(joined.unsigned_tx.input[0], tx.input[0]);
+        assert_eq!(joined.unsigned_tx.input[1], other.unsigned_tx.input[0]);
+    }
+
+    #[test]
+    fn test_wait_for_new_block() {
+        let server = MockServer::with_result(json!({
+            "hash": "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+            "height": 0,
+        }));
+        let rpc = client(&server);
+
+        assert_eq!(rpc.wait_for_new_block(None).unwrap().height, 0);
+        assert_eq!(server.last_request().params(), &json!([]));
+        assert_eq!(rpc.wait_for_new_block(Some(0)).unwrap().height, 0);
+        assert_eq!(server.last_request().params(), &json!([0]));
+    }
+
+    #[test]
+    fn test_wallet_lock() {
+        let unlocked_until = Arc::new(AtomicUsize::new(0));
+        let state = unlocked_until.clone();
+        let server = MockServer::start(move |req| match req.method() {
+            "walletpassphrase" if req.params()[0] == "passphrase" => {
+                state.store(1_700_000_060, Ordering::SeqCst);
+                req.result(Value::Null)
+            }
+            "walletpassphrase" | "walletpassphrasechange" if req.params()[0] != "passphrase" => {
+                req.error(-14, "Error: The wallet passphrase entered was incorrect.")
+            }
+            "walletlock" => {
+                state.store(0, Ordering::SeqCst);
+                req.result(Value::Null)
+            }
+            "getwalletinfo" => {
+                let mut info = fixture("getwalletinfo.json");
+                info["unlocked_until"] = json!(state.load(Ordering::SeqCst));
+                req.result(info)
+            }
+            _ => req.result(Value::Null),
+        });
+        let rpc = client(&server).wallet("hot");
+
+        assert!(matches!(
+            rpc.wallet_passphrase(&Secret::from("wrong"), 60),
+            Err(Error::WalletPassphraseIncorrect)
+        ));
+        assert_eq!(rpc.get_wallet_info().unwrap().unlocked_until, Some(0));
+
+        rpc.wallet_passphrase(&Secret::from("passphrase"), 60)
+            .unwrap();
+        assert_eq!(
+            rpc.get_wallet_info().unwrap().unlocked_until,
+            Some(1_700_000_060)
+        );
+
+        rpc.wallet_lock().unwrap();
+        assert_eq!(server.last_request().params(), &json!([]));
+        assert_eq!(rpc.get_wallet_info().unwrap().unlocked_until, Some(0));
+
+        rpc.wallet_passphrase_change(&Secret::from("passphrase"), &Secret::from("new"))
+            .unwrap();
+        assert_eq!(
+            server.last_request().params(),
+            &json!(["passphrase", "new"])
+        );
+        assert_eq!(unlocked_until.load(Ordering::SeqCst), 0);
+
+        // Walletlock on an unencrypted wallet
+        let server = MockServer::start(|req| {
+            req.error(
+                -15,
+                "Error: running with an unencrypted wallet, but walletlock was called.",
+            )
+        });
+        assert!(matches!(
+            client(&server).wallet_lock(),
+            Err(Error::WalletNotEncrypted)
+        ));
+    }
+
+    #[test]
+    fn test_supports() {
+        let server = MockServer::with_result(fixture("getnetworkinfo.json"));
+        let rpc = client(&server);
+        // v27.0
+        assert!(rpc.supports(CoreFeature::ScanBlocks).unwrap());
+        assert!(!rpc.supports(CoreFeature::SubmitPackage).unwrap());
+        assert_eq!(server.last_request().method(), "getnetworkinfo");
+    }
+
+    #[test]
+    fn test_get_raw_transaction_hex() {
+        let tx = genesis_block(Network::Bitcoin).txdata[0].clone();
+        let hex = encode::serialize_hex(&tx);
+        let server = MockServer::with_result(json!(hex));
+        let rpc = client(&server);
+
+        let txid = tx.compute_txid();
+        assert_eq!(rpc.get_raw_transaction_hex(&txid).unwrap(), hex);
+        assert_eq!(server.last_request().params(), &json!([txid, false]));
+    }
+
+    #[test]
+    fn test_get_tx_out_set_info_with_index() {
+        let server = MockServer::with_result(fixture("gettxoutsetinfo_coinstatsindex.json"));
+        let rpc = client(&server);
+
+        let info = rpc
+            .get_tx_out_set_info_with(
+                Some(TxOutSetHashType::Muhash),
+                Some(HashOrHeight::Height(850_000)),
+                None,
+            )
+            .unwrap();
+        assert_eq!(server.last_request().params(), &json!(["muhash", 850_000]));
+        assert_eq!(info.height, 850_000);
+        assert!(info.muhash.is_some());
+        assert_eq!(info.hash_serialized_3, None);
+        assert_eq!(
+            info.total_unspendable_amount,
+            Some(Amount::from_sat(22_261_964_163))
+        );
+        let block = info.block_info.unwrap();
+        assert_eq!(block.coinbase, Amount::from_sat(326_374_125));
+        assert_eq!(block.prevout_spent, Amount::from_sat(256_349_843_710));
+        assert_eq!(block.unspendables.unclaimed_rewards, Amount::ZERO);
+    }
+
+    #[test]
+    fn test_call_cancellable() {
+        let server = MockServer::start(|req| req.result(json!(1)).delay(Duration::from_secs(5)));
+        let rpc = client(&server);
+        let token = CancelToken::new();
+
+        let canceller = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+
+        let started = Instant::now();
+        let res = rpc.call_cancellable::<u64>("getblockcount", Params::new(), &token);
+        assert!(matches!(res, Err(Error::Cancelled)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        // Already cancelled
+        let res = rpc.call_cancellable::<u64>("getblockcount", Params::new(), &token);
+        assert!(matches!(res, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_call_cancellable_completed() {
+        let server = MockServer::with_result(json!(1));
+        let token = CancelToken::new();
+        let res = client(&server).call_cancellable::<u64>("getblockcount", Params::new(), &token);
+        assert_eq!(res.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_id_mismatch() {
+        let server = MockServer::start(|req| {
+            MockResponse::json(
+                json!({ "result": 1, "error": null, "id": req.body["id"].as_u64().unwrap() + 1 }),
+            )
+        });
+        assert!(matches!(
+            client(&server).get_block_count(),
+            Err(Error::IdMismatch)
+        ));
+
+        // Ids of the following requests
+        let server = MockServer::with_result(json!(1));
+        let rpc = client(&server);
+        rpc.get_block_count().unwrap();
+        rpc.get_block_count().unwrap();
+        let requests = server.requests();
+        assert_ne!(requests[0].body["id"], requests[1].body["id"]);
+    }
+
+    #[test]
+    fn test_clones_share_connections() {
+        let server = MockServer::with_result(json!(850_000));
+        let rpc = client(&server);
+
+        rpc.get_block_count().unwrap();
+        rpc.clone().get_block_count().unwrap();
+        // Different URL path and timeout, same HTTP client
+        rpc.wallet("hot").get_block_count().unwrap();
+        rpc.with_timeout(Duration::from_secs(5))
+            .get_block_count()
+            .unwrap();
+        assert_eq!(server.requests().len(), 4);
+        assert_eq!(server.connections(), 1);
+
+        // Not shared with a new client
+        client(&server).get_block_count().unwrap();
+        assert_eq!(server.connections(), 2);
+    }
+
+    #[test]
+    fn test_estimate_raw_fee() {
+        let server = MockServer::with_result(fixture("estimaterawfee.json"));
+        let rpc = client(&server);
+
+        let estimate = rpc.estimate_raw_fee(24, Some(0.85)).unwrap();
+        assert_eq!(server.last_request().params(), &json!([24, 0.85]));
+
+        // Target above the short horizon max
+        assert!(estimate.short.is_none());
+
+        let medium = estimate.medium.unwrap();
+        assert_eq!(medium.fee_rate, Some(FeeRate::from_sat_per_kwu(3_087)));
+        assert_eq!(medium.scale, 2);
+        let pass = medium.pass.unwrap();
+        assert_eq!(pass.start_range, 12_154.0);
+        assert_eq!(pass.within_target, 412.73);
+        assert_eq!(medium.fail.unwrap().end_range, 12_154.0);
+        assert!(medium.errors.is_empty());
+
+        let long = estimate.long.unwrap();
+        assert_eq!(long.fee_rate, None);
+        assert!(long.pass.is_none());
+        assert!(long.fail.is_some());
+        assert_eq!(long.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_get_raw_transactions() {
+        let tx = genesis_block(Network::Bitcoin).txdata[0].clone();
+        let txid = tx.compute_txid();
+        let server = MockServer::start(move |req| {
+            req.batch(|_, params| {
+                if params[0] == json!(txid) {
+                    Ok(json!(encode::serialize_hex(&genesis_block(Network::Bitcoin).txdata[0])))
+                } else {
+                    Err((-5, "No such mempool or blockchain transaction. Use gettransaction for wallet transactions."))
+                }
+            })
+        });
+        let rpc = client(&server);
+
+        let missing = Txid::from_str(&"d4".repeat(32)).unwrap();
+        let res = rpc.get_raw_transactions(&[txid, missing, txid]).unwrap();
+        // Single request
+        assert_eq!(server.requests().len(), 1);
+        let calls = server.last_request().body;
+        assert_eq!(calls[1]["method"], "getrawtransaction");
+        assert_eq!(calls[1]["params"], json!([missing, false]));
+
+        assert_eq!(res.len(), 3);
+        assert_eq!(res[0].as_ref().unwrap(), &tx);
+        assert!(matches!(res[1], Err(Error::NotFoundRpc { code: -5, .. })));
+        assert_eq!(res[2].as_ref().unwrap(), &tx);
+
+        assert!(rpc.get_raw_transactions(&[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use std::fmt;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Io(std::io::Error),
+    Reqwest(reqwest::Error),
+    SerdeJson(serde_json::Error),
+    FailedToDeserialize(String),
+    /// Cookie file content isn't in the `<username>:<password>` format
+    InvalidCookie,
+    /// Request cancelled with a [`CancelToken`](crate::CancelToken)
+    Cancelled,
+    /// The thread running a cancellable request ended without a result
+    Disconnected,
+    /// Response body exceeds the `max_response_bytes` limit
+    ResponseTooLarge,
+    /// Response without both `result` and `error`
+    BadResult,
+    /// `null` result where a value was expected
+    NullResult,
+    /// Response `id` different from the request one
+    IdMismatch,
+    /// JSON-RPC error
+    Rpc {
+        code: i32,
+        message: String,
+    },
+    /// `getblock` verbosity above `3`
+    InvalidVerbosity(u8),
+    /// Coinbase outputs below the block subsidy, so the fees are unknown
+    CoinbaseBelowSubsidy,
+    /// The requested object (block, transaction, ...) doesn't exist
+    ///
+    /// Mapped from the RPC error `code` that the called method uses for this condition.
+    NotFoundRpc {
+        code: i32,
+        message: String,
+    },
+    /// Wrong wallet passphrase (RPC error `-14`)
+    WalletPassphraseIncorrect,
+    /// Wallet passphrase call on a not encrypted wallet (RPC error `-15`)
+    WalletNotEncrypted,
+    /// Unlock of an already unlocked wallet, rejected by old nodes (RPC error `-17`)
+    WalletAlreadyUnlocked,
+    /// Index not enabled (`height` is `None`) or not synced yet
+    IndexNotReady {
+        name: String,
+        synced: bool,
+        height: Option<u32>,
+    },
+    /// Block submission rejected, with the reason (i.e. `duplicate`)
+    BlockRejected(String),
+    /// Block proposal rejected, with the reason (i.e. `bad-txnmrklroot`)
+    BlockProposalRejected(String),
+    /// The chain kept changing during a read that requires a consistent view
+    Reorg,
+    Unauthorized,
+    BadRequest,
+    Forbidden,
+    NotFound,
+    MethodNotAllowed,
+    TooManyRequests,
+    UnhandledClientError,
+    InternalServerError,
+    NotImplemented,
+    BadGateway,
+    ServiceUnavailable,
+    GatewayTimeout,
+    UnhandledServerError,
+    #[cfg(feature = "zmq")]
+    Zmq(::zmq::Error),
+}
+
+impl Error {
+    /// Check if the error is transient: connection failure, `503`, `504` or node warming up
+    /// (RPC error `-28`)
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Reqwest(e) => e.is_connect(),
+            Self::ServiceUnavailable | Self::GatewayTimeout => true,
+            Self::Rpc { code, .. } => *code == -28,
+            _ => false,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Reqwest(e) => Some(e),
+            Self::SerdeJson(e) => Some(e),
+            #[cfg(feature = "zmq")]
+            Self::Zmq(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io: {e}"),
+            Self::Reqwest(e) => write!(f, "reqwest: {e}"),
+            Self::SerdeJson(e) => write!(f, "json: {e}"),
+            Self::FailedToDeserialize(e) => write!(f, "failed to deserialize: {e}"),
+            Self::InvalidCookie => write!(f, "invalid cookie file"),
+            Self::Cancelled => write!(f, "request cancelled"),
+            Self::Disconnected => write!(f, "request thread ended without a result"),
+            Self::ResponseTooLarge => write!(f, "response too large"),
+            Self::BadResult => write!(f, "response without result"),
+            Self::NullResult => write!(f, "unexpected null result"),
+            Self::IdMismatch => write!(f, "response id mismatch"),
+            Self::Rpc { code, message } => write!(f, "RPC error {code}: {message}"),
+            Self::NotFoundRpc { code, message } => {
+                write!(f, "not found (RPC error {code}): {message}")
+            }
+            Self::WalletPassphraseIncorrect => write!(f, "incorrect wallet passphrase"),
+            Self::WalletNotEncrypted => write!(f, "wallet not encrypted"),
+            Self::WalletAlreadyUnlocked => write!(f, "wallet already unlocked"),
+            Self::IndexNotReady {
+                name, height: None, ..
+            } => write!(f, "{name} not enabled"),
+            Self::IndexNotReady {
+                name,
+                height: Some(height),
+                ..
+            } => write!(f, "{name} not synced (height {height})"),
+            Self::BlockRejected(reason) => write!(f, "block rejected: {reason}"),
+            Self::BlockProposalRejected(reason) => write!(f, "block proposal rejected: {reason}"),
+            Self::CoinbaseBelowSubsidy => write!(f, "coinbase outputs below the block subsidy"),
+            Self::Reorg => write!(f, "chain reorganized during the read"),
+            Self::InvalidVerbosity(verbosity) => write!(f, "invalid verbosity: {verbosity}"),
+            Self::Unauthorized => write!(f, "unauthorized"),
+            Self::BadRequest => write!(f, "bad request"),
+            Self::Forbidden => write!(f, "forbidden"),
+            Self::NotFound => write!(f, "not found"),
+            Self::MethodNotAllowed => write!(f, "method not allowed"),
+            Self::TooManyRequests => write!(f, "too many requests"),
+            Self::UnhandledClientError => write!(f, "unhandled client error"),
+            Self::InternalServerError => write!(f, "internal server error"),
+            Self::NotImplemented => write!(f, "not implemented"),
+            Self::BadGateway => write!(f, "bad gateway"),
+            Self::ServiceUnavailable => write!(f, "service unavailable"),
+            Self::GatewayTimeout => write!(f, "gateway timeout"),
+            Self::UnhandledServerError => write!(f, "unhandled server error"),
+            #[cfg(feature = "zmq")]
+            Self::Zmq(e) => write!(f, "zmq: {e}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Reqwest(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::SerdeJson(err)
+    }
+}
+
+#[cfg(feature = "zmq")]
+impl From<::zmq::Error> for Error {
+    fn from(err: ::zmq::Error) -> Self {
+        Error::Zmq(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Error::Rpc {
+                code: -8,
+                message: String::from("Block height out of range"),
+            }
+            .to_string(),
+            "RPC error -8: Block height out of range"
+        );
+        assert_eq!(
+            Error::NotFoundRpc {
+                code: -5,
+                message: String::from("Block not found"),
+            }
+            .to_string(),
+            "not found (RPC error -5): Block not found"
+        );
+        assert_eq!(
+            Error::IndexNotReady {
+                name: String::from("txindex"),
+                synced: false,
+                height: None,
+            }
+            .to_string(),
+            "txindex not enabled"
+        );
+        assert_eq!(
+            Error::IndexNotReady {
+                name: String::from("txindex"),
+                synced: false,
+                height: Some(420_000),
+            }
+            .to_string(),
+            "txindex not synced (height 420000)"
+        );
+        assert_eq!(
+            Error::BlockRejected(String::from("duplicate")).to_string(),
+            "block rejected: duplicate"
+        );
+        assert_eq!(Error::Unauthorized.to_string(), "unauthorized");
+        assert_eq!(
+            Error::WalletPassphraseIncorrect.to_string(),
+            "incorrect wallet passphrase"
+        );
+    }
+
+    #[test]
+    fn test_source() {
+        let e = Error::from(std::io::Error::other("cookie"));
+        assert_eq!(e.to_string(), "io: cookie");
+        assert_eq!(e.source().unwrap().to_string(), "cookie");
+
+        let e = Error::from(serde_json::from_str::<u8>("x").unwrap_err());
+        assert!(e.to_string().starts_with("json: "));
+        assert!(e.source().is_some());
+
+        assert!(Error::Reorg.source().is_none());
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Fee rate conversions
+//!
+//! Bitcoin Core reports fee rates in BTC/kvB (i.e. `estimatesmartfee`) but accepts `fee_rate` params in sat/vB.
+
+use bitcoin::{Amount, FeeRate};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Convert a BTC/kvB value to [`FeeRate`] (rounded up to the next sat/kwu)
+///
+/// Return `None` if the value is negative or not a valid amount.
+pub fn from_btc_per_kvb(btc_per_kvb: f64) -> Option<FeeRate> {
+    let sat_per_kvb: u64 = Amount::from_btc(btc_per_kvb).ok()?.to_sat();
+    Some(FeeRate::from_sat_per_kwu(sat_per_kvb.div_ceil(4)))
+}
+
+/// Convert [`FeeRate`] to BTC/kvB
+pub fn to_btc_per_kvb(fee_rate: FeeRate) -> f64 {
+    Amount::from_sat(fee_rate.to_sat_per_kwu().saturating_mul(4)).to_btc()
+}
+
+/// Convert [`FeeRate`] to sat/vB
+pub fn to_sat_per_vb(fee_rate: FeeRate) -> f64 {
+    fee_rate.to_sat_per_kwu() as f64 / 250.0
+}
+
+/// Serde (de)serialization of [`FeeRate`] as BTC/kvB
+pub mod as_btc_per_kvb {
+    use super::*;
+
+    pub fn serialize<S>(fee_rate: &FeeRate, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_f64(to_btc_per_kvb(*fee_rate))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<FeeRate, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let btc_per_kvb: f64 = f64::deserialize(deserializer)?;
+        from_btc_per_kvb(btc_per_kvb)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid fee rate: {btc_per_kvb}")))
+    }
+
+    pub mod opt {
+        use super::*;
+
+        pub fn serialize<S>(fee_rate: &Option<FeeRate>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match fee_rate {
+                Some(fee_rate) => super::serialize(fee_rate, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<FeeRate>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            match Option::<f64>::deserialize(deserializer)? {
+                Some(btc_per_kvb) => from_btc_per_kvb(btc_per_kvb).map(Some).ok_or_else(|| {
+                    serde::de::Error::custom(format!("invalid fee rate: {btc_per_kvb}"))
+                }),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+/// Serde serialization of [`FeeRate`] as sat/vB (`fee_rate` params)
+pub mod as_sat_per_vb {
+    use super::*;
+
+    pub fn serialize<S>(fee_rate: &FeeRate, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_f64(to_sat_per_vb(*fee_rate))
+    }
+
+    pub mod opt {
+        use super::*;
+
+        pub fn serialize<S>(fee_rate: &Option<FeeRate>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match fee_rate {
+                Some(fee_rate) => super::serialize(fee_rate, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_btc_per_kvb() {
+        assert_eq!(from_btc_per_kvb(0.0), Some(FeeRate::ZERO));
+        // 1 sat/vB
+        assert_eq!(
+            from_btc_per_kvb(0.00001),
+            Some(FeeRate::from_sat_per_vb_u32(1))
+        );
+        // Rounded up to the next sat/kwu
+        assert_eq!(
+            from_btc_per_kvb(0.00000001),
+            Some(FeeRate::from_sat_per_kwu(1))
+        );
+        assert_eq!(
+            from_btc_per_kvb(0.00001001),
+            Some(FeeRate::from_sat_per_kwu(251))
+        );
+        assert_eq!(
+            from_btc_per_kvb(0.00001004),
+            Some(FeeRate::from_sat_per_kwu(251))
+        );
+
+        assert_eq!(from_btc_per_kvb(-0.00001), None);
+        assert_eq!(from_btc_per_kvb(f64::NAN), None);
+        assert_eq!(from_btc_per_kvb(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn test_to_btc_per_kvb() {
+        assert_eq!(to_btc_per_kvb(FeeRate::ZERO), 0.0);
+        assert_eq!(to_btc_per_kvb(FeeRate::from_sat_per_kwu(1)), 0.00000004);
+        assert_eq!(to_btc_per_kvb(FeeRate::from_sat_per_vb_u32(1)), 0.00001);
+        // Saturated instead of overflowing
+        assert_eq!(to_btc_per_kvb(FeeRate::MAX), Amount::MAX.to_btc());
+
+        for sat_per_kwu in [1, 250, 251, 12_345, 1_000_000] {
+            let fee_rate = FeeRate::from_sat_per_kwu(sat_per_kwu);
+            assert_eq!(from_btc_per_kvb(to_btc_per_kvb(fee_rate)), Some(fee_rate));
+        }
+    }
+
+    #[test]
+    fn test_to_sat_per_vb() {
+        assert_eq!(to_sat_per_vb(FeeRate::ZERO), 0.0);
+        assert_eq!(to_sat_per_vb(FeeRate::from_sat_per_kwu(1)), 0.004);
+        assert_eq!(to_sat_per_vb(FeeRate::from_sat_per_vb_u32(1)), 1.0);
+        assert_eq!(to_sat_per_vb(FeeRate::from_sat_per_kwu(2_563)), 10.252);
+    }
+
+    #[test]
+    fn test_serde() {
+        #[derive(serde::Serialize, Deserialize)]
+        struct Estimate {
+            #[serde(with = "as_btc_per_kvb")]
+            fee_rate: FeeRate,
+            #[serde(default, with = "as_btc_per_kvb::opt")]
+            opt: Option<FeeRate>,
+        }
+
+        let estimate: Estimate = serde_json::from_str(r#"{"fee_rate":0.00012}"#).unwrap();
+        assert_eq!(estimate.fee_rate, FeeRate::from_sat_per_vb_u32(12));
+        assert_eq!(estimate.opt, None);
+        assert!(serde_json::from_str::<Estimate>(r#"{"fee_rate":-1.0}"#).is_err());
+
+        let json: String = serde_json::to_string(&estimate).unwrap();
+        assert_eq!(json, r#"{"fee_rate":0.00012,"opt":null}"#);
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! JSON-RPC body building and response parsing, shared by the clients
+
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+
+use crate::builder::RequestHook;
+use crate::{Error, Params};
+
+/// `result` is `None` if missing, `Some(None)` if `null`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+struct GenericResult<T> {
+    #[serde(default = "missing", deserialize_with = "deserialize_present")]
+    result: Option<Option<T>>,
+    error: Option<RpcError>,
+}
+
+fn missing<T>() -> Option<Option<T>> {
+    None
+}
+
+/// Deserialize a field that is present (`null` included)
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ErrorResponse {
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IdResponse {
+    /// `None` if missing, `Some(Value::Null)` if `null`
+    #[serde(default, deserialize_with = "deserialize_some")]
+    id: Option<Value>,
+    error: Option<Value>,
+}
+
+fn deserialize_some<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// Build request body
+///
+/// `params` are sent as an array, or as an object if there are named params.
+pub(crate) fn build_body(id: u64, method: &str, params: Params) -> Result<String, Error> {
+    Ok(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": params.into_value()?,
+    })
+    .to_string())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BatchResponseItem {
+    id: Option<usize>,
+    #[serde(default = "missing", deserialize_with = "deserialize_present")]
+    result: Option<Option<Value>>,
+    error: Option<RpcError>,
+}
+
+/// Build batch request body
+///
+/// The index of each call is used as its `id`.
+pub(crate) fn build_batch_body(calls: Vec<(String, Params)>) -> Result<String, Error> {
+    let calls: Vec<Value> = calls
+        .into_iter()
+        .enumerate()
+        .map(|(id, (method, params))| {
+            Ok(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params.into_value()?,
+            }))
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(Value::Array(calls).to_string())
+}
+
+/// Deserialize batch response, matching the results to `methods` by `id`
+///
+/// A `null` result is returned as [`Value::Null`]. Missing results are [`Error::BadResult`].
+pub(crate) fn deserialize_batch(
+    methods: &[String],
+    data: &str,
+) -> Result<Vec<Result<Value, Error>>, Error> {
+    let items: Vec<BatchResponseItem> =
+        serde_json::from_str(data).map_err(|e| Error::FailedToDeserialize(e.to_string()))?;
+
+    let mut results: Vec<Result<Value, Error>> =
+        methods.iter().map(|_| Err(Error::BadResult)).collect();
+    for item in items.into_iter() {
+        let Some(id) = item.id.filter(|id| *id < methods.len()) else {
+            continue;
+        };
+        results[id] = match item.error {
+            Some(RpcError { code, message }) => Err(map_error(&methods[id], code, message)),
+            None => match item.result {
+                Some(result) => Ok(result.unwrap_or(Value::Null)),
+                None => Err(Error::BadResult),
+            },
+        };
+    }
+    Ok(results)
+}
+
+/// Check if the RPC error `code` means that the requested object doesn't exist
+fn is_not_found(method: &str, code: i32) -> bool {
+    matches!(
+        (method, code),
+        ("getblockhash", -8)
+            | (
+                "getblock"
+                    | "getblockheader"
+                    | "getblockstats"
+                    | "getrawtransaction"
+                    | "getmempoolentry",
+                -5
+            )
+    )
+}
+
+/// Map the RPC errors that have a dedicated [`Error`] variant
+fn map_rpc_error(method: &str, code: i32, message: &str) -> Option<Error> {
+    if is_not_found(method, code) {
+        return Some(Error::NotFoundRpc {
+            code,
+            message: message.to_string(),
+        });
+    }
+    match (method, code) {
+        ("walletpassphrase" | "walletpassphrasechange", -14) => {
+            Some(Error::WalletPassphraseIncorrect)
+        }
+        ("walletpassphrase" | "walletpassphrasechange" | "walletlock", -15) => {
+            Some(Error::WalletNotEncrypted)
+        }
+        ("walletpassphrase", -17) => Some(Error::WalletAlreadyUnlocked),
+        _ => None,
+    }
+}
+
+/// Map any RPC error, falling back to [`Error::Rpc`]
+fn map_error(method: &str, code: i32, message: String) -> Error {
+    match map_rpc_error(method, code, &message) {
+        Some(e) => e,
+        None => Error::Rpc { code, message },
+    }
+}
+
+/// Check the JSON-RPC error object of the response, if any
+///
+/// Must be called before [`check_status`], since Bitcoin Core replies to failed calls with HTTP errors.
+pub(crate) fn check_error(method: &str, data: &str) -> Result<(), Error> {
+    if let Ok(ErrorResponse {
+        error: Some(RpcError { code, message }),
+    }) = serde_json::from_str::<ErrorResponse>(data)
+    {
+        return Err(map_error(method, code, message));
+    }
+    Ok(())
+}
+
+/// Check that the response `id` matches the request one
+///
+/// A `null` id is accepted only with an error (the request id couldn't be read by the server).
+/// Bodies without an id (i.e. the JSON error of a proxy) or that aren't JSON are left to the
+/// other checks.
+pub(crate) fn check_id(id: u64, data: &str) -> Result<(), Error> {
+    match serde_json::from_str::<IdResponse>(data) {
+        Ok(IdResponse {
+            id: Some(Value::Null),
+            error: Some(..),
+        }) => Ok(()),
+        Ok(IdResponse {
+            id: Some(res_id), ..
+        }) if res_id != id => Err(Error::IdMismatch),
+        _ => Ok(()),
+    }
+}
+
+/// Notify the outcome of a request to the hook and, with the `tracing` feature, emit an event
+pub(crate) fn notify<T>(
+    hook: Option<&RequestHook>,
+    method: &str,
+    param_count: usize,
+    duration: Duration,
+    result: &Result<T, Error>,
+) {
+    #[cfg(feature = "tracing")]
+    match result {
+        Ok(..) => tracing::debug!(method, param_count, ?duration, "RPC call succeeded"),
+        Err(e) => tracing::debug!(method, param_count, ?duration, error = ?e, "RPC call failed"),
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = param_count;
+
+    if let Some(hook) = hook {
+        hook.call(method, duration, result.as_ref().map(|_| ()));
+    }
+}
+
+/// Map HTTP status code to [`Error`]
+pub(crate) fn check_status(status: u16) -> Result<(), Error> {
+    match status {
+        0_u16..=399_u16 => Ok(()),
+        400 => Err(Error::BadRequest),
+        401 => Err(Error::Unauthorized),
+        402 => Err(Error::UnhandledClientError),
+        403 => Err(Error::Forbidden),
+        404 => Err(Error::NotFound),
+        405 => Err(Error::MethodNotAllowed),
+        406_u16..=428_u16 => Err(Error::UnhandledClientError),
+        429 => Err(Error::TooManyRequests),
+        430_u16..=499_u16 => Err(Error::UnhandledClientError),
+        500 => Err(Error::InternalServerError),
+        501 => Err(Error::NotImplemented),
+        502 => Err(Error::BadGateway),
+        503 => Err(Error::ServiceUnavailable),
+        504 => Err(Error::GatewayTimeout),
+        _ => Err(Error::UnhandledServerError),
+    }
+}
+
+pub(crate) fn deserialize<T>(method: &str, data: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    match deserialize_opt::<T>(method, data)? {
+        Some(result) => Ok(result),
+        // `null` is a valid value of some types (i.e. `Option`)
+        None => serde_json::from_value(Value::Null).map_err(|_| Error::NullResult),
+    }
+}
+
+/// Deserialize response of RPCs that may return a `null` result
+pub(crate) fn deserialize_opt<T>(method: &str, data: &str) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    match serde_json::from_str::<GenericResult<T>>(data) {
+        Ok(GenericResult {
+            error: Some(RpcError { code, message }),
+            ..
+        }) => Err(map_error(method, code, message)),
+        Ok(GenericResult {
+            result: Some(result),
+            ..
+        }) => Ok(result),
+        Ok(GenericResult { result: None, .. }) => Err(Error::BadResult),
+        Err(error) => Err(Error::FailedToDeserialize(error.to_string())),
+    }
+}
+
+/// Deserialize the `result` of a batched call, like [`deserialize`] does for the single calls
+pub(crate) fn deserialize_result<T>(result: Value) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let null: bool = result.is_null();
+    serde_json::from_value(result).map_err(|e| match null {
+        true => Error::NullResult,
+        false => Error::FailedToDeserialize(e.to_string()),
+    })
+}
+
+/// Deserialize response of RPCs that return `null` on success
+pub(crate) fn deserialize_null(method: &str, data: &str) -> Result<(), Error> {
+    deserialize_opt::<Value>(method, data).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_id() {
+        // Matching
+        assert!(check_id(7, r#"{"result":1,"error":null,"id":7}"#).is_ok());
+        // Wrong
+        assert!(matches!(
+            check_id(7, r#"{"result":1,"error":null,"id":8}"#),
+            Err(Error::IdMismatch)
+        ));
+        assert!(matches!(
+            check_id(7, r#"{"result":1,"error":null,"id":"7"}"#),
+            Err(Error::IdMismatch)
+        ));
+        // `null` with an error
+        let data = r#"{"result":null,"error":{"code":-32700,"message":"Parse error"},"id":null}"#;
+        assert!(check_id(7, data).is_ok());
+        // `null` without an error
+        assert!(matches!(
+            check_id(7, r#"{"result":1,"error":null,"id":null}"#),
+            Err(Error::IdMismatch)
+        ));
+        // Not a JSON-RPC response
+        assert!(check_id(7, r#"{"message":"bad gateway"}"#).is_ok());
+        assert!(check_id(7, "<html>Bad Gateway</html>").is_ok());
+    }
+
+    fn error_response(code: i32, message: &str) -> String {
+        json!({ "result": null, "error": { "code": code, "message": message }, "id": 0 })
+            .to_string()
+    }
+
+    #[test]
+    fn test_rpc_errors() {
+        let res = deserialize_opt::<Value>(
+            "getrawtransaction",
+            &error_response(-5, "No such mempool or blockchain transaction"),
+        );
+        assert!(matches!(res, Err(Error::NotFoundRpc { code: -5, .. })));
+
+        let res = deserialize::<Value>(
+            "getblockhash",
+            &error_response(-8, "Block height out of range"),
+        );
+        assert!(matches!(res, Err(Error::NotFoundRpc { code: -8, .. })));
+
+        // Not a not found error for the other methods
+        let res = deserialize_null(
+            "getblockstats",
+            &error_response(-8, "Invalid selected statistic"),
+        );
+        assert!(matches!(res, Err(Error::Rpc { code: -8, .. })));
+
+        let res = deserialize_null(
+            "sendrawtransaction",
+            &error_response(-25, "bad-txns-inputs-missingorspent"),
+        );
+        assert!(matches!(res, Err(Error::Rpc { code: -25, .. })));
+
+        let res = deserialize_null(
+            "sendrawtransaction",
+            &error_response(-26, "min relay fee not met"),
+        );
+        match res {
+            Err(Error::Rpc { code, message }) => {
+                assert_eq!(code, -26);
+                assert_eq!(message, "min relay fee not met");
+            }
+            res => panic!("unexpected {res:?}"),
+        }
+
+        let data: String = error_response(-28, "Loading block index...");
+        let err = check_error("getblockcount", &data).unwrap_err();
+        assert!(matches!(err, Error::Rpc { code: -28, .. }));
+        assert!(err.is_transient());
+        assert!(matches!(
+            deserialize::<u64>("getblockcount", &data),
+            Err(Error::Rpc { code: -28, .. })
+        ));
+    }
+
+    #[test]
+    fn test_null_result() {
+        let data = r#"{"result":null,"error":null,"id":0}"#;
+        assert_eq!(deserialize_opt::<u64>("gettxout", data).unwrap(), None);
+        assert_eq!(deserialize::<Option<u64>>("gettxout", data).unwrap(), None);
+        assert!(matches!(
+            deserialize::<u64>("getblockcount", data),
+            Err(Error::NullResult)
+        ));
+        assert!(deserialize_null("walletlock", data).is_ok());
+
+        // Neither result nor error
+        assert!(matches!(
+            deserialize_opt::<u64>("getblockcount", r#"{"id":0}"#),
+            Err(Error::BadResult)
+        ));
+    }
+}
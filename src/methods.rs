@@ -0,0 +1,1733 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! RPC methods, shared by the blocking and the async clients
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::builder::Timeout;
+use crate::Error;
+use bitcoin::consensus::encode::{self, Decodable};
+use bitcoin::psbt::Psbt;
+
+/// Implement the RPC methods for a client
+///
+/// The client must have a `transport` field and implement `call_jsonrpc` and `call_batch`.
+/// `asyncness` and `await` are empty for the blocking client, and `examples` enables the
+/// doc examples (`all` to enable, `any` to disable).
+macro_rules! impl_rpc_methods {
+    (
+        client: $client:ident,
+        asyncness: [$($async:tt)?],
+        await: [$($await:tt)*],
+        examples: $examples:ident $(,)?
+    ) => {
+        const _: () = {
+            use std::collections::HashMap;
+            use std::time::Duration;
+
+            use bitcoin::address::NetworkUnchecked;
+            use bitcoin::consensus::encode;
+            use bitcoin::hex::{DisplayHex, FromHex};
+            use bitcoin::psbt::Psbt;
+            use bitcoin::{
+                Address, Amount, Block, BlockHash, FeeRate, Network, OutPoint, PrivateKey,
+                PublicKey, SignedAmount, Transaction, TxOut, Txid,
+            };
+            use serde::de::DeserializeOwned;
+            use serde_json::{json, Value};
+
+            use crate::batch::{Batch, BatchHandle, BatchResponse};
+            use crate::builder::{Timeout, TimeoutConfig};
+            use crate::fee;
+            use crate::jsonrpc;
+            use crate::methods::{decode_hex, decode_psbt, locator_heights, wait_timeout};
+            use crate::params::Params;
+            use crate::types::*;
+            use crate::{subsidy_at_height, Error, RetryPolicy, Secret};
+
+            impl $client {
+                /// Get a client for the wallet endpoint (`<host>/wallet/<name>`)
+                ///
+                /// Required for wallet RPCs when the node has more than one wallet loaded.
+                pub fn wallet(&self, name: &str) -> Self {
+                    let mut client: Self = self.clone();
+                    client.transport.wallet = Some(name.into());
+                    client
+                }
+
+                /// Set the timeout of the calls without a specific one
+                pub fn set_default_timeout(&mut self, timeout: Duration) {
+                    self.transport.timeouts.default = Some(timeout);
+                }
+
+                /// Override all the per-request timeouts
+                pub fn timeouts(mut self, timeouts: TimeoutConfig) -> Self {
+                    self.transport.timeouts = timeouts;
+                    self
+                }
+
+                /// Get a client that uses `timeout` for all the calls, method-specific timeouts included
+                ///
+                #[cfg_attr($examples(), doc = r#"
+```rust,no_run
+use std::time::Duration;
+
+use bitcoin_rpc::Client;
+
+let rpc = Client::new("http://127.0.0.1:8332", "username", "password");
+let info = rpc.with_timeout(Duration::from_secs(2)).get_blockchain_info();
+```"#)]
+                pub fn with_timeout(&self, timeout: Duration) -> Self {
+                    let mut client: Self = self.clone();
+                    client.transport.timeout_override = Some(timeout);
+                    client
+                }
+
+                /// Get a client with a different retry policy (`None` to disable the retries)
+                pub fn with_retry(&self, policy: Option<RetryPolicy>) -> Self {
+                    let mut client: Self = self.clone();
+                    client.transport.retry = policy;
+                    client
+                }
+
+                $($async)? fn request<R>(
+                    &self,
+                    method: &str,
+                    params: Params,
+                    timeout: Timeout,
+                ) -> Result<R, Error>
+                where
+                    R: DeserializeOwned,
+                {
+                    let response = self.call_jsonrpc(method, params, timeout)$($await)*?;
+                    jsonrpc::deserialize::<R>(method, &response)
+                }
+
+                /// Request for RPCs that may return a `null` result
+                $($async)? fn request_opt<R>(
+                    &self,
+                    method: &str,
+                    params: Params,
+                    timeout: Timeout,
+                ) -> Result<Option<R>, Error>
+                where
+                    R: DeserializeOwned,
+                {
+                    let response = self.call_jsonrpc(method, params, timeout)$($await)*?;
+                    jsonrpc::deserialize_opt::<R>(method, &response)
+                }
+
+                /// Request for RPCs that return `null` on success
+                $($async)? fn request_null(
+                    &self,
+                    method: &str,
+                    params: Params,
+                    timeout: Timeout,
+                ) -> Result<(), Error> {
+                    let response = self.call_jsonrpc(method, params, timeout)$($await)*?;
+                    jsonrpc::deserialize_null(method, &response)
+                }
+
+                /// Call any RPC
+                ///
+                /// Params are sent as an object when named params are used (see [`Params`]).
+                ///
+                #[cfg_attr($examples(), doc = r#"
+```rust,no_run
+use bitcoin_rpc::{Client, Params};
+
+let rpc = Client::new("http://127.0.0.1:8332", "username", "password");
+let height: u64 = rpc.call("getblockcount", Params::new()).unwrap();
+```"#)]
+                pub $($async)? fn call<R>(&self, method: &str, params: Params) -> Result<R, Error>
+                where
+                    R: DeserializeOwned,
+                {
+                    let response = self.call_jsonrpc(method, params, Timeout::Default)$($await)*?;
+                    jsonrpc::deserialize::<R>(method, &response)
+                }
+
+                /// Call any RPC and get the untouched `result` field (`null` included)
+                ///
+                /// Useful to read fields not exposed by the typed methods yet.
+                pub $($async)? fn call_value(
+                    &self,
+                    method: &str,
+                    params: Params,
+                ) -> Result<Value, Error> {
+                    let response = self.call_jsonrpc(method, params, Timeout::Default)$($await)*?;
+                    Ok(jsonrpc::deserialize_opt::<Value>(method, &response)?
+                        .unwrap_or(Value::Null))
+                }
+
+                /// Send a [`Batch`] of calls in a single request
+                ///
+                /// A failed call doesn't affect the others: errors are returned by [`BatchResponse::take`].
+                /// The batch is retried according to the [`RetryPolicy`], like the single calls.
+                pub $($async)? fn send_batch(&self, batch: Batch) -> Result<BatchResponse, Error> {
+                    Ok(BatchResponse::new(
+                        self.call_batch(batch.calls, Timeout::Default)$($await)*?,
+                    ))
+                }
+
+                pub $($async)? fn get_blockchain_info(&self) -> Result<BlockchainInfo, Error> {
+                    self.request("getblockchaininfo", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn get_network_info(&self) -> Result<NetworkInfo, Error> {
+                    self.request("getnetworkinfo", Params::new(), Timeout::Default)$($await)*
+                }
+
+                /// Get the node version (i.e. `250000` for v25.0)
+                pub $($async)? fn get_server_version(&self) -> Result<u32, Error> {
+                    Ok(self.get_network_info()$($await)*?.version)
+                }
+
+                /// Check if the node supports a feature
+                pub $($async)? fn supports(&self, feature: CoreFeature) -> Result<bool, Error> {
+                    Ok(feature.is_supported(self.get_server_version()$($await)*?))
+                }
+
+                pub $($async)? fn get_mining_info(&self) -> Result<MiningInfo, Error> {
+                    self.request("getmininginfo", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn get_peer_info(&self) -> Result<Vec<PeerInfo>, Error> {
+                    self.request("getpeerinfo", Params::new(), Timeout::Default)$($await)*
+                }
+
+                /// Get min, median and max ping time of the connected peers
+                pub $($async)? fn peer_latency_summary(&self) -> Result<LatencySummary, Error> {
+                    Ok(LatencySummary::from_peers(&self.get_peer_info()$($await)*?))
+                }
+
+                pub $($async)? fn get_index_info(&self) -> Result<IndexInfo, Error> {
+                    self.request("getindexinfo", Params::new(), Timeout::Default)$($await)*
+                }
+
+                /// Get height, hash and best header height in a single call
+                pub $($async)? fn get_tip(&self) -> Result<Tip, Error> {
+                    Ok(Tip::from(&self.get_blockchain_info()$($await)*?))
+                }
+
+                /// Get the estimated sync progress, clamped to `0.0..=1.0`
+                pub $($async)? fn sync_progress(&self) -> Result<f64, Error> {
+                    let info: BlockchainInfo = self.get_blockchain_info()$($await)*?;
+                    Ok(info.verification_progress.clamp(0.0, 1.0))
+                }
+
+                /// Check if all the known headers have been validated
+                pub $($async)? fn headers_synced(&self) -> Result<bool, Error> {
+                    let info: BlockchainInfo = self.get_blockchain_info()$($await)*?;
+                    Ok(info.blocks >= info.headers)
+                }
+
+                /// Check that `-txindex` is enabled and synced
+                ///
+                /// Useful before [`Self::get_raw_transaction`] without a block hash, to get a clearer error.
+                pub $($async)? fn require_txindex(&self) -> Result<(), Error> {
+                    match self.get_index_info()$($await)*?.txindex {
+                        Some(TxIndex { synced: true, .. }) => Ok(()),
+                        Some(TxIndex {
+                            synced,
+                            best_block_height,
+                        }) => Err(Error::IndexNotReady {
+                            name: String::from("txindex"),
+                            synced,
+                            height: Some(best_block_height),
+                        }),
+                        None => Err(Error::IndexNotReady {
+                            name: String::from("txindex"),
+                            synced: false,
+                            height: None,
+                        }),
+                    }
+                }
+
+                pub $($async)? fn get_block_count(&self) -> Result<u64, Error> {
+                    self.request("getblockcount", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn get_best_block_hash(&self) -> Result<BlockHash, Error> {
+                    self.request("getbestblockhash", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn get_block_hash(
+                    &self,
+                    block_height: u64,
+                ) -> Result<BlockHash, Error> {
+                    self.request(
+                        "getblockhash",
+                        Params::new().push(block_height),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Wait for a new block (or the timeout) and return the current tip
+                ///
+                /// `timeout_ms` defaults to `0` (no timeout).
+                pub $($async)? fn wait_for_new_block(
+                    &self,
+                    timeout_ms: Option<u64>,
+                ) -> Result<WaitForBlockResult, Error> {
+                    self.request(
+                        "waitfornewblock",
+                        Params::new().push_opt(timeout_ms),
+                        wait_timeout(timeout_ms),
+                    )$($await)*
+                }
+
+                /// Wait for a specific block (or the timeout) and return the current tip
+                pub $($async)? fn wait_for_block(
+                    &self,
+                    block_hash: &BlockHash,
+                    timeout_ms: Option<u64>,
+                ) -> Result<WaitForBlockResult, Error> {
+                    self.request(
+                        "waitforblock",
+                        Params::new().push(block_hash).push_opt(timeout_ms),
+                        wait_timeout(timeout_ms),
+                    )$($await)*
+                }
+
+                /// Wait for the chain to reach `height` (or the timeout) and return the current tip
+                pub $($async)? fn wait_for_block_height(
+                    &self,
+                    height: u64,
+                    timeout_ms: Option<u64>,
+                ) -> Result<WaitForBlockResult, Error> {
+                    self.request(
+                        "waitforblockheight",
+                        Params::new().push(height).push_opt(timeout_ms),
+                        wait_timeout(timeout_ms),
+                    )$($await)*
+                }
+
+                /// Get a block locator: hashes from the tip back to genesis, exponentially spaced
+                /// after the first 11 (as built by Bitcoin Core)
+                ///
+                /// The hashes are fetched in a single batch, retried if the tip changes in the
+                /// meantime. Return [`Error::Reorg`] if it keeps changing after a few attempts.
+                pub $($async)? fn get_block_locator(&self) -> Result<Vec<BlockHash>, Error> {
+                    const MAX_ATTEMPTS: usize = 3;
+
+                    for _ in 0..MAX_ATTEMPTS {
+                        let tip: u64 = self.get_block_count()$($await)*?;
+                        let mut batch = Batch::new();
+                        let handles: Vec<BatchHandle<BlockHash>> = locator_heights(tip)
+                            .into_iter()
+                            .map(|height| batch.get_block_hash(height))
+                            .collect();
+                        let best = batch.get_best_block_hash();
+
+                        let mut res: BatchResponse = self.send_batch(batch)$($await)*?;
+                        let hashes: Vec<BlockHash> = handles
+                            .into_iter()
+                            .map(|handle| res.take(handle))
+                            .collect::<Result<_, Error>>()?;
+
+                        // The calls of a batch are executed in order: if the tip is still the
+                        // best block at the end, the chain didn't change during the batch
+                        if hashes.first() == Some(&res.take(best)?) {
+                            return Ok(hashes);
+                        }
+                    }
+
+                    Err(Error::Reorg)
+                }
+
+                /// Get block with the given `verbosity` (`0` to `3`, see [`GetBlockResponse`])
+                ///
+                /// Return [`Error::InvalidVerbosity`] above `3`, without calling the node.
+                pub $($async)? fn get_block_verbosity(
+                    &self,
+                    block_hash: &BlockHash,
+                    verbosity: u8,
+                ) -> Result<GetBlockResponse, Error> {
+                    if verbosity > 3 {
+                        return Err(Error::InvalidVerbosity(verbosity));
+                    }
+
+                    let params = || Params::new().push(block_hash).push(verbosity);
+                    let timeout: Timeout = self.transport.timeouts.block_fetch.into();
+                    match verbosity {
+                        0 => Ok(GetBlockResponse::Hex(
+                            self.request("getblock", params(), timeout)$($await)*?,
+                        )),
+                        1 => Ok(GetBlockResponse::Verbose(
+                            self.request("getblock", params(), timeout)$($await)*?,
+                        )),
+                        2 => {
+                            let block: VerboseBlock =
+                                self.request("getblock", params(), timeout)$($await)*?;
+                            Ok(GetBlockResponse::Decoded(block.to_block()?))
+                        }
+                        _ => Ok(GetBlockResponse::VerboseWithPrevouts(
+                            self.request("getblock", params(), timeout)$($await)*?,
+                        )),
+                    }
+                }
+
+                pub $($async)? fn get_block(&self, block_hash: &BlockHash) -> Result<Block, Error> {
+                    match self.get_block_verbosity(block_hash, 2)$($await)*? {
+                        GetBlockResponse::Decoded(block) => Ok(block),
+                        _ => Err(Error::BadResult),
+                    }
+                }
+
+                /// Get the block at `height`, checking that it's still in the active chain after the fetch
+                ///
+                /// Retry if a reorg replaces the block in the meantime. Return [`Error::Reorg`] if the
+                /// chain keeps changing after a few attempts.
+                pub $($async)? fn get_block_at_height_consistent(
+                    &self,
+                    height: u64,
+                ) -> Result<Block, Error> {
+                    const MAX_ATTEMPTS: usize = 3;
+
+                    for _ in 0..MAX_ATTEMPTS {
+                        let hash: BlockHash = self.get_block_hash(height)$($await)*?;
+                        let block: Block = self.get_block(&hash)$($await)*?;
+                        if self.get_block_hash(height)$($await)*? == hash {
+                            return Ok(block);
+                        }
+                    }
+
+                    Err(Error::Reorg)
+                }
+
+                /// Get block metadata and txids (`getblock` verbosity 1)
+                pub $($async)? fn get_block_info(
+                    &self,
+                    block_hash: &BlockHash,
+                ) -> Result<BlockInfo, Error> {
+                    self.request(
+                        "getblock",
+                        Params::new().push(block_hash).push(1),
+                        self.transport.timeouts.block_fetch.into(),
+                    )$($await)*
+                }
+
+                pub $($async)? fn get_block_hex(
+                    &self,
+                    block_hash: &BlockHash,
+                ) -> Result<String, Error> {
+                    match self.get_block_verbosity(block_hash, 0)$($await)*? {
+                        GetBlockResponse::Hex(hex) => Ok(hex),
+                        _ => Err(Error::BadResult),
+                    }
+                }
+
+                /// Get the raw block bytes, without consensus decoding
+                pub $($async)? fn get_block_raw(
+                    &self,
+                    block_hash: &BlockHash,
+                ) -> Result<Vec<u8>, Error> {
+                    let hex: String = self.get_block_hex(block_hash)$($await)*?;
+                    Vec::from_hex(&hex).map_err(|e| Error::FailedToDeserialize(e.to_string()))
+                }
+
+                /// Submit a raw block
+                pub $($async)? fn submit_block_raw(&self, block: &[u8]) -> Result<(), Error> {
+                    match self
+                        .request_opt::<String>(
+                            "submitblock",
+                            Params::new().push(block.to_lower_hex_string()),
+                            Timeout::Default,
+                        )$($await)*?
+                    {
+                        None => Ok(()),
+                        Some(reason) => Err(Error::BlockRejected(reason)),
+                    }
+                }
+
+                /// Get the coinbase transaction of a block
+                pub $($async)? fn get_coinbase(
+                    &self,
+                    block_hash: &BlockHash,
+                ) -> Result<Transaction, Error> {
+                    let hex: String = self.get_block_hex(block_hash)$($await)*?;
+                    let mut block: Block = decode_hex(&hex)?;
+                    if block.txdata.is_empty() {
+                        return Err(Error::BadResult);
+                    }
+                    Ok(block.txdata.swap_remove(0))
+                }
+
+                /// Get the block reward, split into subsidy and fees
+                ///
+                /// Height and block are fetched in a single batch request. Return
+                /// [`Error::CoinbaseBelowSubsidy`] if the miner didn't claim the whole subsidy.
+                pub $($async)? fn get_block_subsidy_and_fees(
+                    &self,
+                    block_hash: &BlockHash,
+                    network: Network,
+                ) -> Result<BlockReward, Error> {
+                    let mut batch = Batch::new();
+                    let header: BatchHandle<BlockHeight> = batch
+                        .call("getblockheader", Params::new().push(block_hash).push(true));
+                    let hex: BatchHandle<String> =
+                        batch.call("getblock", Params::new().push(block_hash).push(0));
+                    let mut res = BatchResponse::new(
+                        self.call_batch(
+                            batch.calls,
+                            self.transport.timeouts.block_fetch.into(),
+                        )$($await)*?,
+                    );
+
+                    let height: u64 = res.take(header)?.height;
+                    let block: Block = decode_hex(&res.take(hex)?)?;
+                    let coinbase: &Transaction =
+                        block.txdata.first().ok_or(Error::BadResult)?;
+                    let total: Amount = coinbase.output.iter().map(|o| o.value).sum();
+                    let subsidy: Amount = subsidy_at_height(height, network);
+                    Ok(BlockReward {
+                        subsidy,
+                        fees: total
+                            .checked_sub(subsidy)
+                            .ok_or(Error::CoinbaseBelowSubsidy)?,
+                    })
+                }
+
+                /// Get block statistics (all if `stats` is `None`)
+                pub $($async)? fn get_block_stats(
+                    &self,
+                    target: BlockStatsTarget,
+                    stats: Option<&[&str]>,
+                ) -> Result<BlockStats, Error> {
+                    self.request(
+                        "getblockstats",
+                        Params::new().push(target).push_opt(stats),
+                        self.transport.timeouts.block_fetch.into(),
+                    )$($await)*
+                }
+
+                /// Get the 10th, 25th, 50th, 75th and 90th percentile fee rates (sat/vB) of a block
+                ///
+                /// All zeros for blocks without transactions other than the coinbase.
+                pub $($async)? fn get_feerate_percentiles(
+                    &self,
+                    target: BlockStatsTarget,
+                ) -> Result<[u64; 5], Error> {
+                    let stats: BlockStats = self
+                        .get_block_stats(target, Some(&["feerate_percentiles"]))$($await)*?;
+                    Ok(stats.feerate_percentiles.unwrap_or_default())
+                }
+
+                pub $($async)? fn get_raw_mempool(&self) -> Result<Vec<Txid>, Error> {
+                    self.request(
+                        "getrawmempool",
+                        Params::new(),
+                        self.transport.timeouts.block_fetch.into(),
+                    )$($await)*
+                }
+
+                pub $($async)? fn get_mempool_info(&self) -> Result<MempoolInfo, Error> {
+                    self.request("getmempoolinfo", Params::new(), Timeout::Default)$($await)*
+                }
+
+                /// Min relay fee rate (`-minrelaytxfee`)
+                pub $($async)? fn get_min_relay_fee(&self) -> Result<FeeRate, Error> {
+                    Ok(self.get_mempool_info()$($await)*?.min_relay_tx_fee)
+                }
+
+                /// Min fee rate for a transaction to be accepted in the mempool
+                pub $($async)? fn get_mempool_min_fee(&self) -> Result<FeeRate, Error> {
+                    Ok(self.get_mempool_info()$($await)*?.mempool_min_fee)
+                }
+
+                pub $($async)? fn get_raw_transaction(
+                    &self,
+                    txid: &Txid,
+                ) -> Result<Transaction, Error> {
+                    self.request(
+                        "getrawtransaction",
+                        Params::new().push(txid).push(true),
+                        self.transport.timeouts.block_fetch.into(),
+                    )$($await)*
+                }
+
+                /// Get many transactions in a single batch request
+                ///
+                /// The result has the same order of `txids`: a missing transaction doesn't fail the others.
+                pub $($async)? fn get_raw_transactions(
+                    &self,
+                    txids: &[Txid],
+                ) -> Result<Vec<Result<Transaction, Error>>, Error> {
+                    let calls: Vec<(String, Params)> = txids
+                        .iter()
+                        .map(|txid| {
+                            (
+                                String::from("getrawtransaction"),
+                                Params::new().push(txid).push(false),
+                            )
+                        })
+                        .collect();
+                    Ok(self
+                        .call_batch(calls, self.transport.timeouts.block_fetch.into())$($await)*?
+                        .into_iter()
+                        .map(|res| {
+                            let hex: String = jsonrpc::deserialize_result(res?)?;
+                            decode_hex(&hex)
+                        })
+                        .collect())
+                }
+
+                /// Get the consensus-encoded transaction hex, without decoding it
+                pub $($async)? fn get_raw_transaction_hex(
+                    &self,
+                    txid: &Txid,
+                ) -> Result<String, Error> {
+                    self.request(
+                        "getrawtransaction",
+                        Params::new().push(txid).push(false),
+                        self.transport.timeouts.block_fetch.into(),
+                    )$($await)*
+                }
+
+                /// Get a transaction with its chain context (block hash, confirmations, ...)
+                pub $($async)? fn get_raw_transaction_verbose(
+                    &self,
+                    txid: &Txid,
+                ) -> Result<VerboseTransaction, Error> {
+                    self.request(
+                        "getrawtransaction",
+                        Params::new().push(txid).push(2),
+                        self.transport.timeouts.block_fetch.into(),
+                    )$($await)*
+                }
+
+                /// Get a transaction included in `block_hash`
+                ///
+                /// Works also on nodes without `-txindex`.
+                pub $($async)? fn get_raw_transaction_in_block(
+                    &self,
+                    txid: &Txid,
+                    block_hash: &BlockHash,
+                ) -> Result<Transaction, Error> {
+                    let hex: String = self
+                        .request(
+                            "getrawtransaction",
+                            Params::new().push(txid).push(false).push(block_hash),
+                            self.transport.timeouts.block_fetch.into(),
+                        )$($await)*?;
+                    decode_hex(&hex)
+                }
+
+                /// Get an unspent transaction output (`None` if spent or not existing)
+                pub $($async)? fn get_tx_out(
+                    &self,
+                    txid: &Txid,
+                    vout: u32,
+                    include_mempool: bool,
+                ) -> Result<Option<TxOut>, Error> {
+                    let result: Option<GetTxOutResult> = self
+                        .request_opt(
+                            "gettxout",
+                            Params::new().push(txid).push(vout).push(include_mempool),
+                            Timeout::Default,
+                        )$($await)*?;
+                    Ok(result.map(TxOut::from))
+                }
+
+                /// Get many unspent transaction outputs, in a single batch request
+                ///
+                /// The result has the same order of `outpoints`, with `None` for spent or not existing outputs.
+                pub $($async)? fn get_tx_outs(
+                    &self,
+                    outpoints: &[(Txid, u32)],
+                    include_mempool: bool,
+                ) -> Result<Vec<Option<TxOut>>, Error> {
+                    let mut batch = Batch::new();
+                    let handles: Vec<BatchHandle<Option<GetTxOutResult>>> = outpoints
+                        .iter()
+                        .map(|(txid, vout)| {
+                            batch.call(
+                                "gettxout",
+                                Params::new().push(txid).push(vout).push(include_mempool),
+                            )
+                        })
+                        .collect();
+
+                    // Spent outputs have a `null` result
+                    let mut res: BatchResponse = self.send_batch(batch)$($await)*?;
+                    handles
+                        .into_iter()
+                        .map(|handle| Ok(res.take(handle)?.map(TxOut::from)))
+                        .collect()
+                }
+
+                pub $($async)? fn get_zmq_notifications(
+                    &self,
+                ) -> Result<Vec<ZmqNotification>, Error> {
+                    self.request("getzmqnotifications", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn get_difficulty(&self) -> Result<f64, Error> {
+                    self.request("getdifficulty", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn get_tx_out_set_info(&self) -> Result<TxOutSetInfo, Error> {
+                    self.request(
+                        "gettxoutsetinfo",
+                        Params::new(),
+                        self.transport.timeouts.heavy_calls.into(),
+                    )$($await)*
+                }
+
+                /// Get UTXO set info
+                ///
+                /// The `-coinstatsindex` is used (if enabled and `use_index` isn't `false`) only with
+                /// [`TxOutSetHashType::Muhash`] or [`TxOutSetHashType::None`], making the call almost instant.
+                /// `hash_or_height` requires the index.
+                pub $($async)? fn get_tx_out_set_info_with(
+                    &self,
+                    hash_type: Option<TxOutSetHashType>,
+                    hash_or_height: Option<HashOrHeight>,
+                    use_index: Option<bool>,
+                ) -> Result<TxOutSetInfo, Error> {
+                    self.request(
+                        "gettxoutsetinfo",
+                        Params::new()
+                            .push_opt(hash_type)
+                            .push_opt(hash_or_height)
+                            .push_opt(use_index),
+                        self.transport.timeouts.heavy_calls.into(),
+                    )$($await)*
+                }
+
+                /// Import a public key as watch-only (legacy wallets only)
+                ///
+                /// With `rescan` enabled (default) the node scans the whole chain before replying, so no timeout is applied.
+                pub $($async)? fn import_pubkey(
+                    &self,
+                    pubkey: &PublicKey,
+                    label: Option<&str>,
+                    rescan: Option<bool>,
+                ) -> Result<(), Error> {
+                    self.request_null(
+                        "importpubkey",
+                        Params::new()
+                            .push(pubkey.to_string())
+                            .push(label.unwrap_or_default())
+                            .push(rescan.unwrap_or(true)),
+                        Timeout::Unlimited,
+                    )$($await)*
+                }
+
+                /// List wallet transactions since `block_hash` (all if `None`)
+                ///
+                /// Feed [`SinceBlockResult::last_block`] back as `block_hash` on the next poll.
+                pub $($async)? fn list_since_block(
+                    &self,
+                    block_hash: Option<&BlockHash>,
+                    target_confirmations: Option<u32>,
+                    include_watchonly: Option<bool>,
+                    include_removed: Option<bool>,
+                ) -> Result<SinceBlockResult, Error> {
+                    self.request(
+                        "listsinceblock",
+                        Params::new()
+                            .push_opt(block_hash)
+                            .push_opt(target_confirmations)
+                            .push_opt(include_watchonly)
+                            .push_opt(include_removed),
+                        self.transport.timeouts.block_fetch.into(),
+                    )$($await)*
+                }
+
+                /// Fill the keypool
+                ///
+                /// Encrypted wallets must be unlocked first.
+                pub $($async)? fn keypool_refill(
+                    &self,
+                    new_size: Option<u64>,
+                ) -> Result<(), Error> {
+                    self.request_null(
+                        "keypoolrefill",
+                        Params::new().push_opt(new_size),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Flush the keypool and generate new keys (legacy wallets only)
+                ///
+                /// Encrypted wallets must be unlocked first.
+                pub $($async)? fn new_keypool(&self) -> Result<(), Error> {
+                    self.request_null("newkeypool", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn get_addresses_by_label(
+                    &self,
+                    label: &str,
+                ) -> Result<HashMap<Address<NetworkUnchecked>, AddressPurpose>, Error> {
+                    self.request(
+                        "getaddressesbylabel",
+                        Params::new().push(label),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                pub $($async)? fn list_labels(
+                    &self,
+                    purpose: Option<LabelPurpose>,
+                ) -> Result<Vec<String>, Error> {
+                    self.request(
+                        "listlabels",
+                        Params::new().push_opt(purpose),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                pub $($async)? fn list_address_groupings(
+                    &self,
+                ) -> Result<Vec<Vec<AddressGrouping>>, Error> {
+                    self.request("listaddressgroupings", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn set_label(
+                    &self,
+                    address: &Address,
+                    label: &str,
+                ) -> Result<(), Error> {
+                    self.request_null(
+                        "setlabel",
+                        Params::new().push(address.to_string()).push(label),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Get a new receiving address
+                ///
+                /// The address isn't checked against any network: use [`Address::require_network`].
+                pub $($async)? fn get_new_address(
+                    &self,
+                    label: Option<&str>,
+                    address_type: Option<AddressType>,
+                ) -> Result<Address<NetworkUnchecked>, Error> {
+                    self.request(
+                        "getnewaddress",
+                        Params::new().push_opt(label).push_opt(address_type),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Get a new receiving address with `label`
+                pub $($async)? fn get_new_address_with_label(
+                    &self,
+                    label: &str,
+                    address_type: Option<AddressType>,
+                ) -> Result<Address<NetworkUnchecked>, Error> {
+                    self.get_new_address(Some(label), address_type)$($await)*
+                }
+
+                /// Get a new change address, for raw transactions
+                pub $($async)? fn get_raw_change_address(
+                    &self,
+                    address_type: Option<AddressType>,
+                ) -> Result<Address<NetworkUnchecked>, Error> {
+                    self.request(
+                        "getrawchangeaddress",
+                        Params::new().push_opt(address_type),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// List wallet descriptors
+                ///
+                /// With `private` set to `true` the descriptors contain the private keys: handle the result as sensitive data.
+                pub $($async)? fn list_descriptors(
+                    &self,
+                    private: Option<bool>,
+                ) -> Result<ListDescriptorsResult, Error> {
+                    self.request(
+                        "listdescriptors",
+                        Params::new().push_opt(private),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                pub $($async)? fn estimate_smart_fee(
+                    &self,
+                    conf_target: u16,
+                    estimate_mode: Option<EstimateMode>,
+                ) -> Result<SmartFeeEstimate, Error> {
+                    self.request(
+                        "estimatesmartfee",
+                        Params::new().push(conf_target).push_opt(estimate_mode),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Get the raw fee estimates of each time horizon
+                ///
+                /// `threshold` is the success rate required by a bucket (default: `0.95`).
+                pub $($async)? fn estimate_raw_fee(
+                    &self,
+                    conf_target: u16,
+                    threshold: Option<f64>,
+                ) -> Result<RawFeeEstimate, Error> {
+                    self.request(
+                        "estimaterawfee",
+                        Params::new().push(conf_target).push_opt(threshold),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Get the smallest confirmation target (up to `max_target`) for which the estimated fee rate is <= `fee_rate`
+                ///
+                /// All the targets are estimated in a single batch request. The target of the
+                /// estimates is used, since the node may estimate a higher one (i.e. `2` for `1`).
+                /// Return `None` if also `max_target` requires an higher fee rate.
+                pub $($async)? fn blocks_to_confirm(
+                    &self,
+                    fee_rate: FeeRate,
+                    max_target: u16,
+                ) -> Result<Option<u16>, Error> {
+                    let mut batch = Batch::new();
+                    let handles: Vec<BatchHandle<SmartFeeEstimate>> = (1..=max_target)
+                        .map(|target| {
+                            batch.call("estimatesmartfee", Params::new().push(target))
+                        })
+                        .collect();
+
+                    let mut res: BatchResponse = self.send_batch(batch)$($await)*?;
+                    let mut found: Option<u16> = None;
+                    for handle in handles {
+                        let estimate: SmartFeeEstimate = res.take(handle)?;
+                        let enough: bool = estimate
+                            .fee_rate
+                            .is_some_and(|estimated| estimated <= fee_rate);
+                        if enough && estimate.blocks <= max_target {
+                            found = Some(
+                                found.map_or(estimate.blocks, |f| f.min(estimate.blocks)),
+                            );
+                        }
+                    }
+
+                    Ok(found)
+                }
+
+                /// Lock (`unlock = false`) or unlock (`unlock = true`) the given outputs
+                ///
+                /// Calling with `unlock = true` and no `outpoints` unlocks all the outputs.
+                pub $($async)? fn lock_unspent(
+                    &self,
+                    unlock: bool,
+                    outpoints: Option<&[OutPoint]>,
+                    persistent: Option<bool>,
+                ) -> Result<bool, Error> {
+                    let outpoints: Option<Vec<JsonOutPoint>> =
+                        outpoints.map(|o| o.iter().copied().map(JsonOutPoint::from).collect());
+                    self.request(
+                        "lockunspent",
+                        Params::new()
+                            .push(unlock)
+                            .push_opt(outpoints)
+                            .push_opt(persistent),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                pub $($async)? fn list_lock_unspent(&self) -> Result<Vec<OutPoint>, Error> {
+                    let outpoints: Vec<JsonOutPoint> = self
+                        .request("listlockunspent", Params::new(), Timeout::Default)$($await)*?;
+                    Ok(outpoints.into_iter().map(OutPoint::from).collect())
+                }
+
+                pub $($async)? fn list_received_by_address(
+                    &self,
+                    minconf: Option<u32>,
+                    include_empty: Option<bool>,
+                    include_watchonly: Option<bool>,
+                    address_filter: Option<&Address>,
+                ) -> Result<Vec<ReceivedByAddress>, Error> {
+                    self.request(
+                        "listreceivedbyaddress",
+                        Params::new()
+                            .push_opt(minconf)
+                            .push_opt(include_empty)
+                            .push_opt(include_watchonly)
+                            .push_opt(address_filter.map(|a| a.to_string())),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                pub $($async)? fn list_received_by_label(
+                    &self,
+                    minconf: Option<u32>,
+                    include_empty: Option<bool>,
+                    include_watchonly: Option<bool>,
+                ) -> Result<Vec<ReceivedByLabel>, Error> {
+                    self.request(
+                        "listreceivedbylabel",
+                        Params::new()
+                            .push_opt(minconf)
+                            .push_opt(include_empty)
+                            .push_opt(include_watchonly),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Import addresses, scripts and keys (legacy wallets only)
+                ///
+                /// May trigger a rescan, so no timeout is applied.
+                pub $($async)? fn import_multi(
+                    &self,
+                    requests: &[ImportMultiRequest],
+                    options: Option<&ImportMultiOptions>,
+                ) -> Result<Vec<ImportMultiResult>, Error> {
+                    self.request(
+                        "importmulti",
+                        Params::new().push(requests).push_opt(options),
+                        Timeout::Unlimited,
+                    )$($await)*
+                }
+
+                /// List the most recent wallet transactions, skipping the first `skip`
+                ///
+                /// If `label` is `None`, transactions of all labels are returned.
+                pub $($async)? fn list_transactions(
+                    &self,
+                    label: Option<&str>,
+                    count: Option<u32>,
+                    skip: Option<u32>,
+                    include_watchonly: Option<bool>,
+                ) -> Result<Vec<WalletTransaction>, Error> {
+                    self.request(
+                        "listtransactions",
+                        Params::new()
+                            .push(label.unwrap_or("*"))
+                            .push_opt(count)
+                            .push_opt(skip)
+                            .push_opt(include_watchonly),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                pub $($async)? fn list_unspent(
+                    &self,
+                    minconf: Option<u32>,
+                    maxconf: Option<u32>,
+                    addresses: Option<&[Address]>,
+                    include_unsafe: Option<bool>,
+                    query_options: Option<&ListUnspentQueryOptions>,
+                ) -> Result<Vec<UnspentOutput>, Error> {
+                    let addresses: Option<Vec<String>> =
+                        addresses.map(|a| a.iter().map(|a| a.to_string()).collect());
+                    self.request(
+                        "listunspent",
+                        Params::new()
+                            .push_opt(minconf)
+                            .push_opt(maxconf)
+                            .push_opt(addresses)
+                            .push_opt(include_unsafe)
+                            .push_opt(query_options),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Get a block template for mining (`rules` must include `segwit`)
+                ///
+                /// If `long_poll_id` is set (from a previous [`BlockTemplate::long_poll_id`]), the node replies
+                /// only when the template changes (new tip or, after a while, new mempool transactions).
+                /// No HTTP timeout is applied when long polling, since the call may block for minutes.
+                pub $($async)? fn get_block_template(
+                    &self,
+                    rules: &[&str],
+                    long_poll_id: Option<&str>,
+                ) -> Result<BlockTemplate, Error> {
+                    let mut template_request = json!({ "rules": rules });
+                    if let Some(long_poll_id) = long_poll_id {
+                        template_request["longpollid"] = long_poll_id.into();
+                    }
+                    let timeout: Timeout = match long_poll_id {
+                        Some(..) => Timeout::Unlimited,
+                        None => Timeout::Default,
+                    };
+                    self.request(
+                        "getblocktemplate",
+                        Params::new().push(template_request),
+                        timeout,
+                    )$($await)*
+                }
+
+                /// Validate a candidate block without submitting it (`getblocktemplate` proposal mode)
+                pub $($async)? fn propose_block(
+                    &self,
+                    block: &Block,
+                    rules: &[&str],
+                ) -> Result<(), Error> {
+                    let template_request = json!({
+                        "mode": "proposal",
+                        "data": encode::serialize_hex(block),
+                        "rules": rules,
+                    });
+                    match self
+                        .request_opt::<String>(
+                            "getblocktemplate",
+                            Params::new().push(template_request),
+                            Timeout::Default,
+                        )$($await)*?
+                    {
+                        None => Ok(()),
+                        Some(reason) => Err(Error::BlockProposalRejected(reason)),
+                    }
+                }
+
+                /// List the names of the currently loaded wallets
+                ///
+                #[cfg_attr($examples(), doc = r#"
+```rust,no_run
+use bitcoin_rpc::Client;
+
+let rpc = Client::new("http://127.0.0.1:8332", "username", "password");
+for name in rpc.list_wallets().unwrap() {
+    let wallet: Client = rpc.wallet(&name);
+    println!("{name}: {:?}", wallet.list_labels(None).unwrap());
+}
+```"#)]
+                pub $($async)? fn list_wallets(&self) -> Result<Vec<String>, Error> {
+                    self.request("listwallets", Params::new(), Timeout::Default)$($await)*
+                }
+
+                /// List the names of the wallets in the wallet directory (loaded or not)
+                pub $($async)? fn list_wallet_dir(&self) -> Result<Vec<String>, Error> {
+                    let dir: WalletDir = self
+                        .request("listwalletdir", Params::new(), Timeout::Default)$($await)*?;
+                    Ok(dir.wallets.into_iter().map(|w| w.name).collect())
+                }
+
+                /// Load a wallet
+                ///
+                /// Loading a big wallet can take a while, so no timeout is applied.
+                pub $($async)? fn load_wallet(
+                    &self,
+                    name: &str,
+                    load_on_startup: Option<bool>,
+                ) -> Result<LoadWalletResult, Error> {
+                    self.request(
+                        "loadwallet",
+                        Params::new().push(name).push_opt(load_on_startup),
+                        Timeout::Unlimited,
+                    )$($await)*
+                }
+
+                /// Restore and load a wallet from a backup file (path on the node filesystem)
+                ///
+                /// Restoring triggers a rescan, so no timeout is applied.
+                pub $($async)? fn restore_wallet(
+                    &self,
+                    wallet_name: &str,
+                    backup_file: &str,
+                    load_on_startup: Option<bool>,
+                ) -> Result<LoadWalletResult, Error> {
+                    self.request(
+                        "restorewallet",
+                        Params::new()
+                            .push(wallet_name)
+                            .push(backup_file)
+                            .push_opt(load_on_startup),
+                        Timeout::Unlimited,
+                    )$($await)*
+                }
+
+                /// Unload a wallet
+                ///
+                /// If `name` is `None`, the wallet of the endpoint is unloaded (see [`Self::wallet`]).
+                pub $($async)? fn unload_wallet(
+                    &self,
+                    name: Option<&str>,
+                    load_on_startup: Option<bool>,
+                ) -> Result<UnloadWalletResult, Error> {
+                    self.request(
+                        "unloadwallet",
+                        Params::new()
+                            .push_opt(name.or(self.transport.wallet.as_deref()))
+                            .push_opt(load_on_startup),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Migrate a legacy wallet to a descriptor wallet (Core v25+)
+                ///
+                /// If `name` is `None`, the wallet of the endpoint is migrated (see [`Self::wallet`]).
+                /// Migration of a big wallet can take a long time, so no timeout is applied.
+                pub $($async)? fn migrate_wallet(
+                    &self,
+                    name: Option<&str>,
+                    passphrase: Option<&Secret>,
+                ) -> Result<MigrateWalletResult, Error> {
+                    self.request(
+                        "migratewallet",
+                        Params::new()
+                            .push_opt(name.or(self.transport.wallet.as_deref()))
+                            .push_opt(passphrase.map(Secret::expose)),
+                        Timeout::Unlimited,
+                    )$($await)*
+                }
+
+                /// Bump the fee of a wallet transaction, returning the replacement as PSBT (not signed or broadcasted)
+                pub $($async)? fn psbt_bump_fee(
+                    &self,
+                    txid: &Txid,
+                    options: Option<&BumpFeeOptions>,
+                ) -> Result<PsbtBumpFeeResult, Error> {
+                    self.request(
+                        "psbtbumpfee",
+                        Params::new().push(txid).push_opt(options),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Create a `nrequired`-of-`keys` multisig address (keys are not added to the wallet)
+                pub $($async)? fn create_multisig(
+                    &self,
+                    nrequired: u32,
+                    keys: &[PublicKey],
+                    address_type: Option<AddressType>,
+                ) -> Result<MultisigResult, Error> {
+                    self.request(
+                        "createmultisig",
+                        Params::new()
+                            .push(nrequired)
+                            .push(keys)
+                            .push_opt(address_type),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Add a `nrequired`-of-`keys` multisig address to the wallet (legacy wallets only)
+                pub $($async)? fn add_multisig_address(
+                    &self,
+                    nrequired: u32,
+                    keys: &[PublicKey],
+                    label: Option<&str>,
+                    address_type: Option<AddressType>,
+                ) -> Result<MultisigResult, Error> {
+                    self.request(
+                        "addmultisigaddress",
+                        Params::new()
+                            .push(nrequired)
+                            .push(keys)
+                            .push_opt(label)
+                            .push_opt(address_type),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Rescan the blockchain for wallet transactions
+                ///
+                /// A full rescan can take hours, so no timeout is applied. Note that the node keeps scanning
+                /// even if the request is dropped: use [`Self::get_rescan_progress`] to follow it
+                /// and [`Self::abort_rescan`] to stop it.
+                pub $($async)? fn rescan_blockchain(
+                    &self,
+                    start_height: Option<u64>,
+                    stop_height: Option<u64>,
+                ) -> Result<RescanBlockchainResult, Error> {
+                    self.request(
+                        "rescanblockchain",
+                        Params::new().push_opt(start_height).push_opt(stop_height),
+                        Timeout::Unlimited,
+                    )$($await)*
+                }
+
+                /// Abort the running wallet rescan
+                pub $($async)? fn abort_rescan(&self) -> Result<bool, Error> {
+                    self.request("abortrescan", Params::new(), Timeout::Default)$($await)*
+                }
+
+                pub $($async)? fn get_wallet_info(&self) -> Result<WalletInfo, Error> {
+                    self.request("getwalletinfo", Params::new(), Timeout::Default)$($await)*
+                }
+
+                /// Get the progress of the running wallet rescan (`None` if not scanning)
+                pub $($async)? fn get_rescan_progress(
+                    &self,
+                ) -> Result<Option<ScanningProgress>, Error> {
+                    Ok(self.get_wallet_info()$($await)*?.scanning)
+                }
+
+                /// Create, sign and (by default) broadcast a transaction
+                pub $($async)? fn send(
+                    &self,
+                    outputs: &SendOutputs,
+                    options: Option<&SendOptions>,
+                ) -> Result<SendResult, Error> {
+                    self.request(
+                        "send",
+                        Params::new()
+                            .push(outputs)
+                            // conf_target, estimate_mode and fee_rate: set in `options`
+                            .push_opt(None::<u16>)
+                            .push_opt(None::<EstimateMode>)
+                            .push_opt(None::<f64>)
+                            .push_opt(options),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Spend all the wallet UTXOs (or the selected `inputs`) to the recipients, with the fee
+                /// deducted from the recipients (Core v24+)
+                pub $($async)? fn send_all(
+                    &self,
+                    recipients: &[SendAllRecipient],
+                    options: Option<&SendAllOptions>,
+                ) -> Result<SendResult, Error> {
+                    self.request(
+                        "sendall",
+                        Params::new()
+                            .push(recipients)
+                            // conf_target, estimate_mode and fee_rate: set in `options`
+                            .push_opt(None::<u16>)
+                            .push_opt(None::<EstimateMode>)
+                            .push_opt(None::<f64>)
+                            .push_opt(options),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Send to multiple addresses in a single transaction
+                pub $($async)? fn send_many(
+                    &self,
+                    amounts: &HashMap<Address<NetworkUnchecked>, Amount>,
+                    options: Option<&SendManyOptions>,
+                ) -> Result<SendManyResult, Error> {
+                    let amounts: HashMap<&Address<NetworkUnchecked>, f64> =
+                        amounts.iter().map(|(a, v)| (a, v.to_btc())).collect();
+                    let default = SendManyOptions::default();
+                    let options: &SendManyOptions = options.unwrap_or(&default);
+                    let subtract_fee_from: Option<&[Address<NetworkUnchecked>]> =
+                        (!options.subtract_fee_from.is_empty())
+                            .then_some(&options.subtract_fee_from);
+                    self.request(
+                        "sendmany",
+                        Params::new()
+                            .push("")
+                            .push(amounts)
+                            .push_opt(options.minconf)
+                            .push_opt(options.comment.as_deref())
+                            .push_opt(subtract_fee_from)
+                            .push_opt(options.replaceable)
+                            .push_opt(options.conf_target)
+                            .push_opt(options.estimate_mode)
+                            .push_opt(options.fee_rate.map(fee::to_sat_per_vb))
+                            .push_opt(options.verbose),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                pub $($async)? fn send_to_address(
+                    &self,
+                    address: &Address,
+                    amount: Amount,
+                    options: Option<&SendToAddressOptions>,
+                ) -> Result<SendToAddressResult, Error> {
+                    let default = SendToAddressOptions::default();
+                    let options: &SendToAddressOptions = options.unwrap_or(&default);
+                    self.request(
+                        "sendtoaddress",
+                        Params::new()
+                            .push(address)
+                            .push(amount.to_btc())
+                            .push_opt(options.comment.as_deref())
+                            .push_opt(options.comment_to.as_deref())
+                            .push_opt(options.subtract_fee_from_amount)
+                            .push_opt(options.replaceable)
+                            .push_opt(options.conf_target)
+                            .push_opt(options.estimate_mode)
+                            .push_opt(options.avoid_reuse)
+                            .push_opt(options.fee_rate.map(fee::to_sat_per_vb))
+                            .push_opt(options.verbose),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Set or generate a new HD seed (legacy wallets only)
+                ///
+                /// If `seed` is `None`, a random seed is generated. The wallet must be unlocked.
+                ///
+                #[cfg_attr($examples(), doc = r#"
+```rust,no_run
+use bitcoin::{Network, PrivateKey};
+use bitcoin_rpc::Client;
+
+let rpc = Client::new("http://127.0.0.1:18443", "username", "password");
+let seed = PrivateKey::from_slice(&[1; 32], Network::Regtest).unwrap();
+rpc.wallet("legacy").set_hd_seed(Some(true), Some(&seed)).unwrap();
+```"#)]
+                pub $($async)? fn set_hd_seed(
+                    &self,
+                    newkeypool: Option<bool>,
+                    seed: Option<&PrivateKey>,
+                ) -> Result<(), Error> {
+                    self.request_null(
+                        "sethdseed",
+                        Params::new()
+                            .push_opt(newkeypool)
+                            .push_opt(seed.map(|s| s.to_wif())),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Set the wallet fee rate, in BTC/kvB
+                ///
+                /// [`Amount::ZERO`] clears the setting.
+                pub $($async)? fn set_tx_fee(&self, fee_rate: Amount) -> Result<bool, Error> {
+                    self.request(
+                        "settxfee",
+                        Params::new().push(fee_rate.to_btc()),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Set the wallet fee rate
+                ///
+                /// [`FeeRate::ZERO`] clears the setting.
+                pub $($async)? fn set_tx_fee_rate(&self, fee_rate: FeeRate) -> Result<bool, Error> {
+                    self.request(
+                        "settxfee",
+                        Params::new().push(fee::to_btc_per_kvb(fee_rate)),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Change the state of a wallet flag (`value` defaults to `true`)
+                pub $($async)? fn set_wallet_flag(
+                    &self,
+                    flag: WalletFlag,
+                    value: Option<bool>,
+                ) -> Result<SetWalletFlagResult, Error> {
+                    self.request(
+                        "setwalletflag",
+                        Params::new().push(flag).push_opt(value),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Sign inputs of a raw transaction with the wallet keys
+                ///
+                /// `prevtxs` are needed only for outputs not known by the node.
+                pub $($async)? fn sign_raw_transaction_with_wallet(
+                    &self,
+                    tx: &Transaction,
+                    prevtxs: Option<&[PrevTx]>,
+                    sighash: Option<SighashType>,
+                ) -> Result<SignResult, Error> {
+                    self.request(
+                        "signrawtransactionwithwallet",
+                        Params::new()
+                            .push(encode::serialize_hex(tx))
+                            .push_opt(prevtxs)
+                            .push_opt(sighash),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Sign a message with the private key of a wallet address
+                ///
+                /// Only legacy (P2PKH) addresses are supported. Return the base64-encoded signature.
+                pub $($async)? fn sign_message(
+                    &self,
+                    address: &Address,
+                    message: &str,
+                ) -> Result<String, Error> {
+                    self.request(
+                        "signmessage",
+                        Params::new().push(address).push(message),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Verify a message signed with [`Self::sign_message`]
+                pub $($async)? fn verify_message(
+                    &self,
+                    address: &Address,
+                    signature: &str,
+                    message: &str,
+                ) -> Result<bool, Error> {
+                    self.request(
+                        "verifymessage",
+                        Params::new().push(address).push(signature).push(message),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Net change of the wallet balance if the transactions were included in a block
+                pub $($async)? fn simulate_raw_transaction(
+                    &self,
+                    txs: &[&Transaction],
+                    include_watchonly: Option<bool>,
+                ) -> Result<SignedAmount, Error> {
+                    let txs: Vec<String> = txs.iter().map(encode::serialize_hex).collect();
+                    let options = include_watchonly.map(|v| json!({ "include_watchonly": v }));
+                    let res: SimulateRawTransactionResult = self
+                        .request(
+                            "simulaterawtransaction",
+                            Params::new().push(txs).push_opt(options),
+                            Timeout::Default,
+                        )$($await)*?;
+                    Ok(res.balance_change)
+                }
+
+                /// Upgrade the wallet to `version` (default: latest)
+                pub $($async)? fn upgrade_wallet(
+                    &self,
+                    version: Option<u32>,
+                ) -> Result<UpgradeWalletResult, Error> {
+                    self.request(
+                        "upgradewallet",
+                        Params::new().push_opt(version),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Create a PSBT funded by the wallet
+                ///
+                /// Inputs are selected automatically if `inputs` is `None`.
+                pub $($async)? fn wallet_create_funded_psbt(
+                    &self,
+                    inputs: Option<&[CreateTxInput]>,
+                    outputs: &CreateTxOutputs,
+                    locktime: Option<u32>,
+                    options: Option<&FundOptions>,
+                    bip32derivs: Option<bool>,
+                ) -> Result<WalletCreateFundedPsbtResult, Error> {
+                    self.request(
+                        "walletcreatefundedpsbt",
+                        Params::new()
+                            .push(inputs.unwrap_or_default())
+                            .push(outputs)
+                            .push_opt(locktime)
+                            .push_opt(options)
+                            .push_opt(bip32derivs),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Display the address on the external signer (i.e. hardware wallet)
+                pub $($async)? fn wallet_display_address(
+                    &self,
+                    address: &Address,
+                ) -> Result<Address<NetworkUnchecked>, Error> {
+                    let res: WalletDisplayAddressResult = self
+                        .request(
+                            "walletdisplayaddress",
+                            Params::new().push(address),
+                            Timeout::Default,
+                        )$($await)*?;
+                    Ok(res.address)
+                }
+
+                /// Unlock the wallet for `timeout_secs` seconds
+                ///
+                /// Return [`Error::WalletPassphraseIncorrect`] if the passphrase is wrong and
+                /// [`Error::WalletNotEncrypted`] if the wallet isn't encrypted. Bitcoin Core just
+                /// updates the timeout of an already unlocked wallet, while older nodes reject the
+                /// call with [`Error::WalletAlreadyUnlocked`].
+                ///
+                #[cfg_attr($examples(), doc = r#"
+```rust,no_run
+use std::str::FromStr;
+
+use bitcoin::{Address, Amount};
+use bitcoin_rpc::{Client, Secret};
+
+let rpc = Client::new("http://127.0.0.1:8332", "username", "password");
+let wallet: Client = rpc.wallet("hot");
+let passphrase = Secret::from("passphrase");
+let address = Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq")
+    .unwrap()
+    .assume_checked();
+
+wallet.wallet_passphrase(&passphrase, 60).unwrap();
+let res = wallet.send_to_address(&address, Amount::from_sat(10_000), None);
+wallet.wallet_lock().unwrap();
+println!("{:?}", res.unwrap());
+```"#)]
+                pub $($async)? fn wallet_passphrase(
+                    &self,
+                    passphrase: &Secret,
+                    timeout_secs: u64,
+                ) -> Result<(), Error> {
+                    self.request_null(
+                        "walletpassphrase",
+                        Params::new().push(passphrase.expose()).push(timeout_secs),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Lock the wallet, removing the decryption key from memory
+                pub $($async)? fn wallet_lock(&self) -> Result<(), Error> {
+                    self.request_null("walletlock", Params::new(), Timeout::Default)$($await)*
+                }
+
+                /// Change the wallet passphrase
+                pub $($async)? fn wallet_passphrase_change(
+                    &self,
+                    old: &Secret,
+                    new: &Secret,
+                ) -> Result<(), Error> {
+                    self.request_null(
+                        "walletpassphrasechange",
+                        Params::new().push(old.expose()).push(new.expose()),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// Update the PSBT with the wallet data, then sign (default) and finalize (default) it
+                pub $($async)? fn wallet_process_psbt(
+                    &self,
+                    psbt: &Psbt,
+                    sign: Option<bool>,
+                    sighash: Option<SighashType>,
+                    bip32derivs: Option<bool>,
+                    finalize: Option<bool>,
+                ) -> Result<WalletProcessPsbtResult, Error> {
+                    self.request(
+                        "walletprocesspsbt",
+                        Params::new()
+                            .push(psbt.to_string())
+                            .push_opt(sign)
+                            .push_opt(sighash)
+                            .push_opt(bip32derivs)
+                            .push_opt(finalize),
+                        Timeout::Default,
+                    )$($await)*
+                }
+
+                /// List the external signers (requires `-signer`)
+                pub $($async)? fn enumerate_signers(&self) -> Result<Vec<ExternalSigner>, Error> {
+                    let res: EnumerateSignersResult = self
+                        .request("enumeratesigners", Params::new(), Timeout::Default)$($await)*?;
+                    Ok(res.signers)
+                }
+
+                /// Convert an unsigned raw transaction to a PSBT
+                ///
+                /// Fails if the transaction has signatures, unless `permit_sig_data` is set (signatures are dropped).
+                pub $($async)? fn convert_to_psbt(
+                    &self,
+                    tx: &Transaction,
+                    permit_sig_data: Option<bool>,
+                ) -> Result<Psbt, Error> {
+                    let psbt: String = self
+                        .request(
+                            "converttopsbt",
+                            Params::new()
+                                .push(encode::serialize_hex(tx))
+                                .push_opt(permit_sig_data),
+                            Timeout::Default,
+                        )$($await)*?;
+                    decode_psbt(&psbt)
+                }
+
+                /// Update the PSBT inputs with the UTXOs data from the UTXO set, the mempool
+                /// or the given `descriptors`
+                pub $($async)? fn utxo_update_psbt(
+                    &self,
+                    psbt: &Psbt,
+                    descriptors: Option<&[ScanObject]>,
+                ) -> Result<Psbt, Error> {
+                    let psbt: String = self
+                        .request(
+                            "utxoupdatepsbt",
+                            Params::new().push(psbt.to_string()).push_opt(descriptors),
+                            Timeout::Default,
+                        )$($await)*?;
+                    decode_psbt(&psbt)
+                }
+
+                /// Join the inputs and the outputs of multiple PSBTs
+                pub $($async)? fn join_psbts(&self, psbts: &[Psbt]) -> Result<Psbt, Error> {
+                    let psbts: Vec<String> =
+                        psbts.iter().map(|psbt| psbt.to_string()).collect();
+                    let psbt: String = self
+                        .request("joinpsbts", Params::new().push(psbts), Timeout::Default)$($await)*?;
+                    decode_psbt(&psbt)
+                }
+            }
+        };
+    };
+}
+
+pub(crate) use impl_rpc_methods;
+
+/// HTTP timeout for the `waitfor*` RPCs: longer than the RPC one, or unlimited if the RPC never times out
+pub(crate) fn wait_timeout(timeout_ms: Option<u64>) -> Timeout {
+    match timeout_ms {
+        None | Some(0) => Timeout::Unlimited,
+        Some(timeout_ms) => Timeout::After(
+            Duration::from_millis(timeout_ms).saturating_add(Duration::from_secs(30)),
+        ),
+    }
+}
+
+/// Heights of a block locator, from `tip` to genesis
+///
+/// Same of `LocatorEntries` of Bitcoin Core: the first 11 blocks, then the step doubles at
+/// each block.
+pub(crate) fn locator_heights(tip: u64) -> Vec<u64> {
+    let mut heights: Vec<u64> = Vec::new();
+    let mut height: u64 = tip;
+    let mut step: u64 = 1;
+    loop {
+        heights.push(height);
+        if height == 0 {
+            return heights;
+        }
+        height = height.saturating_sub(step);
+        if heights.len() > 10 {
+            step = step.saturating_mul(2);
+        }
+    }
+}
+
+/// Decode consensus-encoded hex
+pub(crate) fn decode_hex<T>(hex: &str) -> Result<T, Error>
+where
+    T: Decodable,
+{
+    encode::deserialize_hex(hex).map_err(|e| Error::FailedToDeserialize(e.to_string()))
+}
+
+/// Decode base64 PSBT
+pub(crate) fn decode_psbt(psbt: &str) -> Result<Psbt, Error> {
+    Psbt::from_str(psbt).map_err(|e| Error::FailedToDeserialize(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_timeout() {
+        assert_eq!(wait_timeout(None), Timeout::Unlimited);
+        assert_eq!(wait_timeout(Some(0)), Timeout::Unlimited);
+        assert_eq!(
+            wait_timeout(Some(1000)),
+            Timeout::After(Duration::from_secs(31))
+        );
+        assert!(matches!(wait_timeout(Some(u64::MAX)), Timeout::After(..)));
+    }
+
+    #[test]
+    fn test_locator_heights() {
+        assert_eq!(locator_heights(0), vec![0]);
+        assert_eq!(locator_heights(3), vec![3, 2, 1, 0]);
+        assert_eq!(locator_heights(10), vec![10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+
+        // tip..tip-10, then tip-11, tip-13, tip-17, ...
+        let mut expected: Vec<u64> = (90..=100).rev().collect();
+        expected.extend([89, 87, 83, 75, 59, 27, 0]);
+        assert_eq!(locator_heights(100), expected);
+
+        let heights: Vec<u64> = locator_heights(850_000);
+        assert_eq!(heights.len(), 11 + 20);
+        assert_eq!(heights[11..14], [849_989, 849_987, 849_983]);
+        assert_eq!(heights.last(), Some(&0));
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Mock JSON-RPC server, for tests
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+/// Request received by the [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// `Authorization` header
+    pub authorization: Option<String>,
+    pub body: Value,
+}
+
+impl MockRequest {
+    pub fn method(&self) -> &str {
+        self.body["method"].as_str().unwrap_or_default()
+    }
+
+    pub fn params(&self) -> &Value {
+        &self.body["params"]
+    }
+
+    /// Reply with a `result`, echoing the request id
+    pub fn result(&self, result: Value) -> MockResponse {
+        MockResponse::json(json!({ "result": result, "error": null, "id": self.body["id"] }))
+    }
+
+    /// Reply with an error object and the HTTP status used by Bitcoin Core
+    pub fn error(&self, code: i32, message: &str) -> MockResponse {
+        let status: u16 = match code {
+            -32601 => 404,
+            -32600 | -32700 => 400,
+            _ => 500,
+        };
+        MockResponse::json(json!({
+            "result": null,
+            "error": { "code": code, "message": message },
+            "id": self.body["id"],
+        }))
+        .status(status)
+    }
+
+    /// Reply to a batch request, calling `f` for each call
+    pub fn batch<F>(&self, f: F) -> MockResponse
+    where
+        F: Fn(&str, &Value) -> Result<Value, (i32, &'static str)>,
+    {
+        let items: Vec<Value> = self
+            .body
+            .as_array()
+            .map(|calls| {
+                calls
+                    .iter()
+                    .map(|call| {
+                        let method: &str = call["method"].as_str().unwrap_or_default();
+                        match f(method, &call["params"]) {
+                            Ok(result) => {
+                                json!({ "result": result, "error": null, "id": call["id"] })
+                            }
+                            Err((code, message)) => json!({
+                                "result": null,
+                                "error": { "code": code, "message": message },
+                                "id": call["id"],
+                            }),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        MockResponse::json(Value::Array(items))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    pub fn json(body: Value) -> Self {
+        Self::raw(body.to_string())
+    }
+
+    pub fn raw(body: String) -> Self {
+        Self {
+            status: 200,
+            body,
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Wait before replying
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// Read a JSON fixture of `tests/fixtures`
+pub fn fixture(name: &str) -> Value {
+    let path: String = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// Write a file in the temp dir, unique for the process
+pub fn temp_file(name: &str, content: &str) -> PathBuf {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("bitcoin-rpc-{}-{name}", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+type Handler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+/// HTTP/1.1 server on a random local port, replying with a handler
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url: String = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Arc<Mutex<Vec<MockRequest>>> = Arc::new(Mutex::new(Vec::new()));
+        let connections: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let server_requests = requests.clone();
+        let server_connections = connections.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                server_connections.fetch_add(1, Ordering::SeqCst);
+                let requests = server_requests.clone();
+                let handler = handler.clone();
+                thread::spawn(move || serve(stream, requests, handler));
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            connections,
+        }
+    }
+
+    /// Reply to each call with the same result
+    pub fn with_result(result: Value) -> Self {
+        Self::start(move |req| req.result(result.clone()))
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Last received request
+    pub fn last_request(&self) -> MockRequest {
+        self.requests().pop().expect("no requests")
+    }
+
+    /// Number of accepted TCP connections
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+/// Serve the requests of a keep-alive connection
+fn serve(stream: TcpStream, requests: Arc<Mutex<Vec<MockRequest>>>, handler: Arc<Handler>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut stream = stream;
+    loop {
+        let mut content_length: usize = 0;
+        let mut authorization: Option<String> = None;
+        let mut line = String::new();
+
+        // Request line
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(..) => return,
+            Ok(..) => {}
+        }
+
+        // Headers
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            let header: &str = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                match name.to_ascii_lowercase().as_str() {
+                    "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                    "authorization" => authorization = Some(value.trim().to_string()),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut body: Vec<u8> = vec![0; content_length];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+
+        let request = MockRequest {
+            authorization,
+            body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+        };
+        requests.lock().unwrap().push(request.clone());
+
+        let response: MockResponse = handler(&request);
+        thread::sleep(response.delay);
+        let head: String = format!(
+            "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            response.status,
+            response.body.len()
+        );
+        if stream.write_all(head.as_bytes()).is_err()
+            || stream.write_all(response.body.as_bytes()).is_err()
+        {
+            return;
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! JSON-RPC params builder
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::Error;
+
+/// JSON-RPC params builder
+///
+/// Positional params are sent as an array: omitted (`None`) trailing params are trimmed,
+/// while omitted params followed by other params are sent as `null`.
+///
+/// Named params are sent as an object. If positional params are also pushed,
+/// they are sent in the `args` field of the object (Core v26+).
+///
+/// ```rust
+/// use bitcoin_rpc::Params;
+///
+/// // ["*", 10]
+/// let params = Params::new().push("*").push(10).push_opt(None::<u32>);
+///
+/// // {"conf_target": 6}
+/// let params = Params::new().named("conf_target", 6);
+/// ```
+#[derive(Debug, Default)]
+pub struct Params {
+    positional: Vec<Option<Value>>,
+    named: Map<String, Value>,
+    error: Option<serde_json::Error>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Push a positional param
+    pub fn push<T>(mut self, value: T) -> Self
+    where
+        T: Serialize,
+    {
+        let value: Option<Value> = self.serialize(value);
+        self.positional.push(value);
+        self
+    }
+
+    /// Push an optional positional param
+    pub fn push_opt<T>(mut self, value: Option<T>) -> Self
+    where
+        T: Serialize,
+    {
+        let value: Option<Value> = value.and_then(|v| self.serialize(v));
+        self.positional.push(value);
+        self
+    }
+
+    /// Set a named param
+    pub fn named<T>(mut self, name: &str, value: T) -> Self
+    where
+        T: Serialize,
+    {
+        if let Some(value) = self.serialize(value) {
+            self.named.insert(name.to_string(), value);
+        }
+        self
+    }
+
+    /// Number of params (positional and named)
+    pub fn len(&self) -> usize {
+        self.positional.len() + self.named.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if params will be sent as an object
+    pub fn is_named(&self) -> bool {
+        !self.named.is_empty()
+    }
+
+    fn serialize<T>(&mut self, value: T) -> Option<Value>
+    where
+        T: Serialize,
+    {
+        match serde_json::to_value(value) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.error.get_or_insert(e);
+                None
+            }
+        }
+    }
+
+    /// Build params JSON value
+    pub fn into_value(self) -> Result<Value, Error> {
+        if let Some(e) = self.error {
+            return Err(Error::SerdeJson(e));
+        }
+
+        let mut positional: Vec<Option<Value>> = self.positional;
+        while let Some(None) = positional.last() {
+            positional.pop();
+        }
+        let positional: Vec<Value> = positional
+            .into_iter()
+            .map(|v| v.unwrap_or(Value::Null))
+            .collect();
+
+        if self.named.is_empty() {
+            return Ok(Value::Array(positional));
+        }
+
+        let mut named: Map<String, Value> = self.named;
+        if !positional.is_empty() {
+            named.insert(String::from("args"), Value::Array(positional));
+        }
+        Ok(Value::Object(named))
+    }
+}
+
+impl From<&[Value]> for Params {
+    fn from(params: &[Value]) -> Self {
+        Self {
+            positional: params.iter().cloned().map(Some).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_positional() {
+        let params = Params::new()
+            .push("*")
+            .push_opt(None::<u32>)
+            .push(10)
+            .push_opt(None::<bool>)
+            .push_opt(None::<bool>);
+        assert_eq!(params.len(), 5);
+        assert!(!params.is_named());
+        // Trailing omitted params are trimmed, the others are sent as null
+        assert_eq!(params.into_value().unwrap(), json!(["*", null, 10]));
+
+        let params = Params::new().push_opt(None::<u32>);
+        assert_eq!(params.into_value().unwrap(), json!([]));
+        assert!(Params::new().is_empty());
+    }
+
+    #[test]
+    fn test_named() {
+        let params = Params::new()
+            .named("conf_target", 6)
+            .named("estimate_mode", "economical");
+        assert!(params.is_named());
+        assert_eq!(
+            params.into_value().unwrap(),
+            json!({ "conf_target": 6, "estimate_mode": "economical" })
+        );
+
+        // Positional params go in `args`
+        let params = Params::new()
+            .push("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")
+            .push_opt(None::<f64>)
+            .named("fee_rate", 25);
+        assert_eq!(
+            params.into_value().unwrap(),
+            json!({
+                "fee_rate": 25,
+                "args": ["bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_slice() {
+        // Explicit nulls are kept
+        let params = Params::from([json!(1), Value::Null].as_slice());
+        assert_eq!(params.into_value().unwrap(), json!([1, null]));
+    }
+
+    #[test]
+    fn test_serialize_error() {
+        // Map keys must be strings
+        let map: BTreeMap<(u8, u8), u8> = BTreeMap::from([((1, 2), 3)]);
+        let params = Params::new().push(1).push(map);
+        assert!(matches!(params.into_value(), Err(Error::SerdeJson(..))));
+    }
+}
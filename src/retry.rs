@@ -0,0 +1,120 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Retry of transient failures
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::Error;
+
+/// Retry policy, with exponential backoff and jitter
+///
+/// Every call is retried, non-idempotent ones included: after an ambiguous failure (i.e. a
+/// `503` from a proxy) the call may have been executed anyway. This is harmless for reads and
+/// for `sendrawtransaction` (a rebroadcast), but not for calls like `sendtoaddress`: disable the
+/// retries for them with [`Client::with_retry`](crate::Client::with_retry).
+///
+/// Batches are retried as a whole, if the request fails or if all the calls fail with a
+/// retryable error.
+///
+/// ```rust,no_run
+/// use bitcoin_rpc::{Client, RetryPolicy};
+///
+/// let rpc = Client::builder("http://127.0.0.1:8332", "username", "password")
+///     .retry(RetryPolicy::default())
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Max attempts, the first one included
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled at each retry
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Errors to retry (default: [`Error::is_transient`])
+    pub retry_on: fn(&Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+            retry_on: Error::is_transient,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Check if a failed `attempt` (starting from `1`) must be retried
+    pub(crate) fn should_retry(&self, attempt: u32, error: &Error) -> bool {
+        attempt < self.max_attempts && (self.retry_on)(error)
+    }
+
+    /// Delay before retrying a failed `attempt` (starting from `1`)
+    ///
+    /// Random between half and the full backoff, to avoid retrying in lockstep with other clients.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let backoff: Duration = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        let half: Duration = backoff / 2;
+        let jitter: f64 = (random() % 1000) as f64 / 1000.0;
+        half + half.mul_f64(jitter)
+    }
+}
+
+/// Random number from the std hasher keys, to not depend on `rand`
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_retry() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            ..Default::default()
+        };
+        assert!(policy.should_retry(1, &Error::ServiceUnavailable));
+        assert!(policy.should_retry(2, &Error::GatewayTimeout));
+        assert!(!policy.should_retry(3, &Error::ServiceUnavailable));
+        assert!(!policy.should_retry(1, &Error::Unauthorized));
+
+        let policy = RetryPolicy {
+            retry_on: |e| matches!(e, Error::Reorg),
+            ..policy
+        };
+        assert!(policy.should_retry(1, &Error::Reorg));
+        assert!(!policy.should_retry(1, &Error::ServiceUnavailable));
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            ..Default::default()
+        };
+        for (attempt, backoff) in [(1, 100), (2, 200), (3, 400), (4, 500), (9, 500)] {
+            let backoff = Duration::from_millis(backoff);
+            for _ in 0..20 {
+                let delay = policy.delay(attempt);
+                assert!(
+                    delay >= backoff / 2 && delay <= backoff,
+                    "{attempt}: {delay:?}"
+                );
+            }
+        }
+        // No overflow
+        assert!(policy.delay(u32::MAX) <= policy.max_delay);
+    }
+}
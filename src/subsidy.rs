@@ -0,0 +1,62 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Block subsidy
+
+use bitcoin::{Amount, Network};
+
+/// Block subsidy at `height`
+///
+/// Halved every 150 blocks on regtest and every 210,000 on the other networks, until zero.
+pub fn subsidy_at_height(height: u64, network: Network) -> Amount {
+    let interval: u64 = match network {
+        Network::Regtest => 150,
+        _ => 210_000,
+    };
+    let halvings: u64 = height / interval;
+    if halvings >= 64 {
+        return Amount::ZERO;
+    }
+    Amount::from_sat(Amount::from_int_btc(50).to_sat() >> halvings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mainnet_halvings() {
+        let subsidy = |height| subsidy_at_height(height, Network::Bitcoin).to_sat();
+        assert_eq!(subsidy(0), 5_000_000_000);
+        assert_eq!(subsidy(209_999), 5_000_000_000);
+        assert_eq!(subsidy(210_000), 2_500_000_000);
+        assert_eq!(subsidy(419_999), 2_500_000_000);
+        assert_eq!(subsidy(420_000), 1_250_000_000);
+        assert_eq!(subsidy(840_000), 312_500_000);
+        // Last non-zero subsidy
+        assert_eq!(subsidy(210_000 * 33 - 1), 1);
+        assert_eq!(subsidy(210_000 * 33), 0);
+        assert_eq!(subsidy(210_000 * 64), 0);
+        assert_eq!(subsidy(u64::MAX), 0);
+
+        assert_eq!(
+            subsidy_at_height(210_000, Network::Testnet).to_sat(),
+            2_500_000_000
+        );
+        assert_eq!(
+            subsidy_at_height(210_000, Network::Signet).to_sat(),
+            2_500_000_000
+        );
+    }
+
+    #[test]
+    fn test_regtest_halvings() {
+        let subsidy = |height| subsidy_at_height(height, Network::Regtest).to_sat();
+        assert_eq!(subsidy(0), 5_000_000_000);
+        assert_eq!(subsidy(149), 5_000_000_000);
+        assert_eq!(subsidy(150), 2_500_000_000);
+        assert_eq!(subsidy(299), 2_500_000_000);
+        assert_eq!(subsidy(300), 1_250_000_000);
+        assert_eq!(subsidy(150 * 64), 0);
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Client state and request handling shared by the blocking and the async clients
+//!
+//! Only the actual HTTP I/O is left to the clients.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::auth::Credentials;
+use crate::builder::{ClientBuilder, HttpOptions, RequestHook, Timeout, TimeoutConfig};
+use crate::jsonrpc;
+use crate::params::Params;
+use crate::{Error, RetryPolicy};
+
+#[derive(Debug, Clone)]
+pub(crate) struct Transport {
+    host: String,
+    auth: Credentials,
+    pub wallet: Option<String>,
+    pub http: HttpOptions,
+    pub timeouts: TimeoutConfig,
+    /// Set with `with_timeout`, overrides all the other timeouts
+    pub timeout_override: Option<Duration>,
+    pub retry: Option<RetryPolicy>,
+    on_request: Option<RequestHook>,
+    /// JSON-RPC id of the next request, shared by the clones
+    next_id: Arc<AtomicU64>,
+}
+
+impl Transport {
+    pub fn new(builder: ClientBuilder) -> Self {
+        Self {
+            host: builder.host,
+            auth: Credentials::new(builder.auth),
+            wallet: None,
+            http: builder.http,
+            timeouts: builder.timeouts,
+            timeout_override: None,
+            retry: builder.retry,
+            on_request: builder.on_request,
+            next_id: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn url(&self) -> String {
+        match &self.wallet {
+            Some(name) => format!("{}/wallet/{}", self.host.trim_end_matches('/'), name),
+            None => self.host.clone(),
+        }
+    }
+
+    /// HTTP timeout of a request (`None` for no timeout)
+    pub fn timeout(&self, timeout: Timeout) -> Option<Duration> {
+        self.timeout_override
+            .or_else(|| self.timeouts.resolve(timeout))
+    }
+
+    pub fn credentials(&self) -> Result<Option<(String, String)>, Error> {
+        self.auth.get()
+    }
+
+    /// Check if a request must be sent again after getting `status`
+    ///
+    /// With cookie auth, the cookie is read again if unauthorized: the node may have been
+    /// restarted, rewriting it.
+    pub fn reload_credentials(&self, status: u16) -> bool {
+        if status == 401 && self.auth.is_cookie() {
+            self.auth.reload();
+            return true;
+        }
+        false
+    }
+
+    /// Get the id and the body of a new request
+    pub fn prepare(&self, method: &str, params: Params) -> Result<(u64, String), Error> {
+        let id: u64 = self.next_id.fetch_add(1, Ordering::Relaxed);
+        Ok((id, jsonrpc::build_body(id, method, params)?))
+    }
+
+    /// Check the response of the request `id`, returning its body
+    pub fn check_response(
+        &self,
+        method: &str,
+        id: u64,
+        status: u16,
+        text: String,
+    ) -> Result<String, Error> {
+        jsonrpc::check_id(id, &text)?;
+        jsonrpc::check_error(method, &text)?;
+        jsonrpc::check_status(status)?;
+        Ok(text)
+    }
+
+    /// Check the response of a batch request, returning the results of the single calls
+    pub fn check_batch_response(
+        &self,
+        methods: &[String],
+        status: u16,
+        text: &str,
+    ) -> Result<Vec<Result<Value, Error>>, Error> {
+        jsonrpc::check_status(status)?;
+        jsonrpc::deserialize_batch(methods, text)
+    }
+
+    pub fn notify<T>(
+        &self,
+        method: &str,
+        param_count: usize,
+        elapsed: Duration,
+        res: &Result<T, Error>,
+    ) {
+        jsonrpc::notify(self.on_request.as_ref(), method, param_count, elapsed, res);
+    }
+
+    /// Delay before retrying a failed `attempt` (starting from `1`), if it must be retried
+    pub fn retry_delay<T>(&self, attempt: u32, res: &Result<T, Error>) -> Option<Duration> {
+        match (&self.retry, res) {
+            (Some(retry), Err(e)) if retry.should_retry(attempt, e) => Some(retry.delay(attempt)),
+            _ => None,
+        }
+    }
+
+    /// Delay before retrying a failed batch `attempt` (starting from `1`), if it must be retried
+    ///
+    /// Retried if the request failed or if all the calls failed with a retryable error (i.e.
+    /// node warming up).
+    pub fn batch_retry_delay(
+        &self,
+        attempt: u32,
+        res: &Result<Vec<Result<Value, Error>>, Error>,
+    ) -> Option<Duration> {
+        let retry: &RetryPolicy = self.retry.as_ref()?;
+        let retryable = |e: &Error| retry.should_retry(attempt, e);
+        let must_retry: bool = match res {
+            Ok(results) => {
+                !results.is_empty() && results.iter().all(|r| r.as_ref().is_err_and(retryable))
+            }
+            Err(e) => retryable(e),
+        };
+        must_retry.then(|| retry.delay(attempt))
+    }
+}
+
+/// Response body, failing if it gets bigger than the max size
+pub(crate) struct LimitedBody {
+    max: usize,
+    body: Vec<u8>,
+}
+
+impl LimitedBody {
+    pub fn new(max: usize, content_length: Option<u64>) -> Result<Self, Error> {
+        if content_length.is_some_and(|len| len > max as u64) {
+            return Err(Error::ResponseTooLarge);
+        }
+        Ok(Self {
+            max,
+            body: Vec::new(),
+        })
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        if self.body.len() + chunk.len() > self.max {
+            return Err(Error::ResponseTooLarge);
+        }
+        self.body.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    pub fn into_string(self) -> Result<String, Error> {
+        String::from_utf8(self.body).map_err(|e| Error::FailedToDeserialize(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limited_body() {
+        // Declared length
+        assert!(matches!(
+            LimitedBody::new(4, Some(5)),
+            Err(Error::ResponseTooLarge)
+        ));
+
+        // Chunked, without length
+        let mut body = LimitedBody::new(4, None).unwrap();
+        body.push(b"ab").unwrap();
+        body.push(b"cd").unwrap();
+        assert!(matches!(body.push(b"e"), Err(Error::ResponseTooLarge)));
+        assert_eq!(body.into_string().unwrap(), "abcd");
+
+        let mut body = LimitedBody::new(4, Some(2)).unwrap();
+        body.push(&[0xff, 0xfe]).unwrap();
+        assert!(matches!(
+            body.into_string(),
+            Err(Error::FailedToDeserialize(..))
+        ));
+    }
+}
@@ -0,0 +1,2135 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::block::{Header, Version};
+use bitcoin::consensus::encode;
+use bitcoin::hashes::Hash;
+use bitcoin::hex::DisplayHex;
+use bitcoin::psbt::Psbt;
+use bitcoin::{
+    Address, Amount, Block, BlockHash, CompactTarget, FeeRate, Network, OutPoint, PrivateKey,
+    PublicKey, ScriptBuf, SignedAmount, Transaction, TxMerkleNode, TxOut, Txid, Wtxid,
+};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Chain {
+    Network(Network),
+    /// Chain name not known by this version of the crate
+    Unknown(String),
+}
+
+impl Chain {
+    pub fn network(&self) -> Option<Network> {
+        match self {
+            Self::Network(network) => Some(*network),
+            Self::Unknown(..) => None,
+        }
+    }
+
+    /// Raw chain name, as returned by Bitcoin Core
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Network(network) => network.to_core_arg(),
+            Self::Unknown(chain) => chain.as_str(),
+        }
+    }
+}
+
+impl From<&str> for Chain {
+    fn from(chain: &str) -> Self {
+        match Network::from_core_arg(chain) {
+            Ok(network) => Self::Network(network),
+            Err(..) => Self::Unknown(chain.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Chain {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let chain: String = String::deserialize(deserializer)?;
+        Ok(Self::from(chain.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockchainInfo {
+    pub chain: Chain,
+    pub blocks: u64,
+    pub headers: u64,
+    #[serde(rename = "bestblockhash")]
+    pub best_block_hash: BlockHash,
+    pub difficulty: f64,
+    #[serde(rename = "mediantime")]
+    pub median_time: u64,
+    /// Estimated verification progress (`0.0` to `1.0`)
+    #[serde(rename = "verificationprogress")]
+    pub verification_progress: f64,
+    #[serde(rename = "initialblockdownload")]
+    pub initial_block_download: bool,
+    pub size_on_disk: u64,
+    pub pruned: bool,
+    #[serde(default, deserialize_with = "deserialize_warnings")]
+    pub warnings: Vec<String>,
+}
+
+/// Chain tip summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tip {
+    pub height: u64,
+    pub hash: BlockHash,
+    /// Height of the best known header
+    pub header_height: u64,
+}
+
+impl From<&BlockchainInfo> for Tip {
+    fn from(info: &BlockchainInfo) -> Self {
+        Self {
+            height: info.blocks,
+            hash: info.best_block_hash,
+            header_height: info.headers,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetworkInfo {
+    pub version: u32,
+    #[serde(rename = "networkactive")]
+    pub network_active: bool,
+    #[serde(default, deserialize_with = "deserialize_warnings")]
+    pub warnings: Vec<String>,
+}
+
+/// Features (mainly RPCs) not available in all the Bitcoin Core versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoreFeature {
+    /// `send` RPC
+    Send,
+    /// `sendall` RPC
+    SendAll,
+    /// `simulaterawtransaction` RPC
+    SimulateRawTransaction,
+    /// `migratewallet` RPC
+    MigrateWallet,
+    /// `scanblocks` RPC
+    ScanBlocks,
+    /// `submitpackage` RPC (regtest-only before v28)
+    SubmitPackage,
+    /// `getdescriptoractivity` RPC
+    GetDescriptorActivity,
+}
+
+impl CoreFeature {
+    /// Min Bitcoin Core version, in the `getnetworkinfo` format (i.e. `250000` for v25.0)
+    pub fn min_version(&self) -> u32 {
+        match self {
+            Self::Send => 210000,
+            Self::SendAll => 240000,
+            Self::SimulateRawTransaction => 240000,
+            Self::MigrateWallet => 240000,
+            Self::ScanBlocks => 250000,
+            Self::SubmitPackage => 280000,
+            Self::GetDescriptorActivity => 290000,
+        }
+    }
+
+    /// Check if the feature is supported by the given version
+    pub fn is_supported(&self, version: u32) -> bool {
+        version >= self.min_version()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MiningInfo {
+    pub blocks: u64,
+    /// Weight of the last assembled block, only if a block template was generated
+    #[serde(rename = "currentblockweight")]
+    pub current_block_weight: Option<u64>,
+    /// Number of transactions of the last assembled block, only if a block template was generated
+    #[serde(rename = "currentblocktx")]
+    pub current_block_tx: Option<u64>,
+    pub difficulty: f64,
+    #[serde(rename = "networkhashps")]
+    pub network_hash_ps: f64,
+    #[serde(rename = "pooledtx")]
+    pub pooled_tx: usize,
+    pub chain: Chain,
+    #[serde(default, deserialize_with = "deserialize_warnings")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MempoolInfo {
+    /// If the mempool is fully loaded
+    pub loaded: bool,
+    /// Number of transactions
+    pub size: u64,
+    /// Sum of the transactions virtual sizes
+    pub bytes: u64,
+    /// Memory usage
+    pub usage: u64,
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub total_fee: Option<Amount>,
+    #[serde(rename = "maxmempool")]
+    pub max_mempool: u64,
+    /// Min fee rate for a transaction to be accepted (max of the min relay fee and the dynamic floor)
+    #[serde(rename = "mempoolminfee", with = "crate::fee::as_btc_per_kvb")]
+    pub mempool_min_fee: FeeRate,
+    #[serde(rename = "minrelaytxfee", with = "crate::fee::as_btc_per_kvb")]
+    pub min_relay_tx_fee: FeeRate,
+    #[serde(
+        default,
+        rename = "incrementalrelayfee",
+        with = "crate::fee::as_btc_per_kvb::opt"
+    )]
+    pub incremental_relay_fee: Option<FeeRate>,
+    #[serde(rename = "unbroadcastcount")]
+    pub unbroadcast_count: Option<u64>,
+    #[serde(rename = "fullrbf")]
+    pub full_rbf: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PeerInfo {
+    pub id: u32,
+    pub addr: String,
+    pub network: String,
+    /// Last ping time, in seconds (`None` if not measured yet)
+    #[serde(rename = "pingtime")]
+    pub ping_time: Option<f64>,
+}
+
+/// Ping time stats of the peers with a measured ping
+///
+/// All the durations are zero if `count` is `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl LatencySummary {
+    pub(crate) fn from_peers(peers: &[PeerInfo]) -> Self {
+        let mut pings: Vec<Duration> = peers
+            .iter()
+            .filter_map(|peer| peer.ping_time)
+            .filter_map(|ping| Duration::try_from_secs_f64(ping).ok())
+            .collect();
+        pings.sort();
+
+        let count: usize = pings.len();
+        let median: Duration = match count {
+            0 => Duration::ZERO,
+            n if n % 2 == 0 => (pings[n / 2 - 1] + pings[n / 2]) / 2,
+            n => pings[n / 2],
+        };
+
+        Self {
+            count,
+            min: pings.first().copied().unwrap_or_default(),
+            median,
+            max: pings.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TxIndex {
+    pub synced: bool,
+    pub best_block_height: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexInfo {
+    /// `None` if the node runs without `-txindex`
+    pub txindex: Option<TxIndex>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TxOutSetInfo {
+    pub height: u64,
+    #[serde(rename = "bestblock")]
+    pub best_block: BlockHash,
+    #[serde(rename = "txouts")]
+    pub tx_outs: u64,
+    pub total_amount: f64,
+    /// Only with [`TxOutSetHashType::HashSerialized3`] (default)
+    pub hash_serialized_3: Option<String>,
+    /// Only with [`TxOutSetHashType::Muhash`]
+    pub muhash: Option<String>,
+    /// Only with `-coinstatsindex`
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub total_unspendable_amount: Option<Amount>,
+    /// Only with `-coinstatsindex`
+    pub block_info: Option<TxOutSetBlockInfo>,
+}
+
+/// UTXO set hash type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxOutSetHashType {
+    /// Expensive, can't be computed with the `-coinstatsindex`
+    #[serde(rename = "hash_serialized_3")]
+    HashSerialized3,
+    Muhash,
+    None,
+}
+
+/// Amounts of the unspendable outputs of a block
+#[derive(Debug, Clone, Deserialize)]
+pub struct Unspendables {
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub genesis_block: Amount,
+    /// Outputs overwritten by duplicated transactions (BIP30)
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub bip30: Amount,
+    /// Provably unspendable outputs (i.e. `OP_RETURN`)
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub scripts: Amount,
+    /// Fees and subsidy not claimed by the miner
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub unclaimed_rewards: Amount,
+}
+
+/// UTXO set changes of the block at the given height
+#[derive(Debug, Clone, Deserialize)]
+pub struct TxOutSetBlockInfo {
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub prevout_spent: Amount,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub coinbase: Amount,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub new_outputs_ex_coinbase: Amount,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub unspendable: Amount,
+    pub unspendables: Unspendables,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionCategory {
+    Send,
+    Receive,
+    Generate,
+    Immature,
+    Orphan,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bip125Replaceable {
+    Yes,
+    No,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletTransaction {
+    pub address: Option<Address<NetworkUnchecked>>,
+    pub category: TransactionCategory,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub amount: SignedAmount,
+    pub label: Option<String>,
+    pub vout: u32,
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub fee: Option<SignedAmount>,
+    /// Negative if the transaction conflicts with the best chain
+    pub confirmations: i64,
+    /// Only present for unconfirmed transactions
+    pub trusted: Option<bool>,
+    #[serde(rename = "blockhash")]
+    pub block_hash: Option<BlockHash>,
+    #[serde(rename = "blockheight")]
+    pub block_height: Option<u64>,
+    #[serde(rename = "blockindex")]
+    pub block_index: Option<u32>,
+    #[serde(rename = "blocktime")]
+    pub block_time: Option<u64>,
+    pub txid: Txid,
+    pub wtxid: Option<Wtxid>,
+    #[serde(default, rename = "walletconflicts")]
+    pub wallet_conflicts: Vec<Txid>,
+    pub time: u64,
+    #[serde(rename = "timereceived")]
+    pub time_received: u64,
+    pub comment: Option<String>,
+    #[serde(rename = "bip125-replaceable")]
+    pub bip125_replaceable: Option<Bip125Replaceable>,
+    /// Only present for `send` category
+    pub abandoned: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SinceBlockResult {
+    pub transactions: Vec<WalletTransaction>,
+    /// Transactions removed from the best chain by a reorg (only with `include_removed`, default `true`)
+    #[serde(default)]
+    pub removed: Vec<WalletTransaction>,
+    #[serde(rename = "lastblock")]
+    pub last_block: BlockHash,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelPurpose {
+    Send,
+    Receive,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddressPurpose {
+    /// `send` or `receive`
+    pub purpose: String,
+}
+
+/// Entry of `listaddressgroupings` (`[address, amount, label?]`)
+#[derive(Debug, Clone)]
+pub struct AddressGrouping {
+    pub address: Address<NetworkUnchecked>,
+    pub amount: Amount,
+    pub label: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for AddressGrouping {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct AddressGroupingVisitor;
+
+        impl<'de> Visitor<'de> for AddressGroupingVisitor {
+            type Value = AddressGrouping;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an array of [address, amount, label?]")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let address = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let amount: f64 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let amount = Amount::from_btc(amount).map_err(de::Error::custom)?;
+                let label: Option<String> = seq.next_element()?;
+                Ok(AddressGrouping {
+                    address,
+                    amount,
+                    label,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(AddressGroupingVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AddressType {
+    Legacy,
+    P2shSegwit,
+    Bech32,
+    Bech32m,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletDescriptor {
+    pub desc: String,
+    pub timestamp: u64,
+    pub active: bool,
+    pub internal: Option<bool>,
+    pub range: Option<[u32; 2]>,
+    pub next: Option<u32>,
+    pub next_index: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListDescriptorsResult {
+    pub wallet_name: String,
+    pub descriptors: Vec<WalletDescriptor>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EstimateMode {
+    Unset,
+    Economical,
+    Conservative,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmartFeeEstimate {
+    /// Estimated fee rate (Core reports it in BTC/kvB)
+    #[serde(default, rename = "feerate", with = "crate::fee::as_btc_per_kvb::opt")]
+    pub fee_rate: Option<FeeRate>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// Block number where estimate was found
+    pub blocks: u16,
+}
+
+/// Fee rate bucket statistics of `estimaterawfee`
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeRateBucket {
+    /// Lower bound of the bucket, in sat/kvB
+    #[serde(rename = "startrange")]
+    pub start_range: f64,
+    /// Upper bound of the bucket, in sat/kvB
+    #[serde(rename = "endrange")]
+    pub end_range: f64,
+    /// Transactions confirmed within the target
+    #[serde(rename = "withintarget")]
+    pub within_target: f64,
+    /// Transactions confirmed at any time
+    #[serde(rename = "totalconfirmed")]
+    pub total_confirmed: f64,
+    /// Transactions still in the mempool after the target
+    #[serde(rename = "inmempool")]
+    pub in_mempool: f64,
+    /// Transactions that left the mempool unconfirmed after the target
+    #[serde(rename = "leftmempool")]
+    pub left_mempool: f64,
+}
+
+/// Fee estimate of a single time horizon of `estimaterawfee`
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawFeeHorizon {
+    #[serde(default, rename = "feerate", with = "crate::fee::as_btc_per_kvb::opt")]
+    pub fee_rate: Option<FeeRate>,
+    /// Exponential decay (per block) of the historical moving average
+    pub decay: f64,
+    /// Number of blocks of each bucket of the target
+    pub scale: u32,
+    /// Lowest fee rate bucket that meets the threshold
+    pub pass: Option<FeeRateBucket>,
+    /// Highest fee rate bucket that doesn't meet the threshold
+    pub fail: Option<FeeRateBucket>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+/// Raw fee estimates, per time horizon
+///
+/// A horizon is missing if the target exceeds its max target.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawFeeEstimate {
+    pub short: Option<RawFeeHorizon>,
+    pub medium: Option<RawFeeHorizon>,
+    pub long: Option<RawFeeHorizon>,
+}
+
+/// [`OutPoint`] in the `{"txid": ..., "vout": ...}` shape used by Bitcoin Core
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct JsonOutPoint {
+    pub txid: Txid,
+    pub vout: u32,
+}
+
+impl From<OutPoint> for JsonOutPoint {
+    fn from(outpoint: OutPoint) -> Self {
+        Self {
+            txid: outpoint.txid,
+            vout: outpoint.vout,
+        }
+    }
+}
+
+impl From<JsonOutPoint> for OutPoint {
+    fn from(outpoint: JsonOutPoint) -> Self {
+        Self::new(outpoint.txid, outpoint.vout)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptPubKey {
+    pub hex: ScriptBuf,
+    pub address: Option<Address<NetworkUnchecked>>,
+    #[serde(rename = "type")]
+    pub script_type: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Prevout {
+    pub generated: bool,
+    pub height: u64,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub value: Amount,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VerboseTxIn {
+    /// Only available with `getblock` verbosity 3 and `getrawtransaction` verbosity 2
+    /// (not for coinbase inputs)
+    pub prevout: Option<Prevout>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VerboseBlockTransaction {
+    pub txid: Txid,
+    #[serde(rename = "hash")]
+    pub wtxid: Wtxid,
+    pub size: u64,
+    pub vsize: u64,
+    pub weight: u64,
+    /// Not available for coinbase transactions or when undo data is missing
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub fee: Option<Amount>,
+    #[serde(rename = "hex", deserialize_with = "deserialize_hex")]
+    pub transaction: Transaction,
+    pub vin: Vec<VerboseTxIn>,
+}
+
+/// `getrawtransaction` verbose result
+#[derive(Debug, Clone, Deserialize)]
+pub struct VerboseTransaction {
+    pub txid: Txid,
+    #[serde(rename = "hash")]
+    pub wtxid: Wtxid,
+    pub size: u64,
+    pub vsize: u64,
+    pub weight: u64,
+    /// Only if the undo data is available (Core v25+, not for coinbase transactions)
+    #[serde(default, with = "bitcoin::amount::serde::as_btc::opt")]
+    pub fee: Option<Amount>,
+    #[serde(rename = "hex", deserialize_with = "deserialize_hex")]
+    pub transaction: Transaction,
+    #[serde(default)]
+    pub vin: Vec<VerboseTxIn>,
+    /// Only if the transaction is confirmed
+    #[serde(rename = "blockhash")]
+    pub block_hash: Option<BlockHash>,
+    pub confirmations: Option<u64>,
+    #[serde(rename = "blocktime")]
+    pub block_time: Option<u64>,
+    pub time: Option<u64>,
+    /// Only if the block hash param was given
+    pub in_active_chain: Option<bool>,
+}
+
+/// Transaction entry of a verbose block
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum VerboseBlockTx {
+    /// `getblock` verbosity 1
+    Txid(Txid),
+    /// `getblock` verbosity 2 and 3
+    Transaction(Box<VerboseBlockTransaction>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VerboseBlock {
+    pub hash: BlockHash,
+    /// `-1` if the block is not on the main chain
+    pub confirmations: i64,
+    pub height: u64,
+    pub version: i32,
+    #[serde(rename = "merkleroot")]
+    pub merkle_root: TxMerkleNode,
+    pub time: u32,
+    #[serde(rename = "mediantime")]
+    pub median_time: u64,
+    pub nonce: u32,
+    /// Compact target, hex encoded
+    pub bits: String,
+    pub difficulty: f64,
+    /// Hex encoded
+    #[serde(rename = "chainwork")]
+    pub chain_work: String,
+    #[serde(rename = "nTx")]
+    pub n_tx: u64,
+    pub size: u64,
+    #[serde(rename = "strippedsize")]
+    pub stripped_size: u64,
+    pub weight: u64,
+    #[serde(rename = "previousblockhash")]
+    pub previous_block_hash: Option<BlockHash>,
+    #[serde(rename = "nextblockhash")]
+    pub next_block_hash: Option<BlockHash>,
+    pub tx: Vec<VerboseBlockTx>,
+}
+
+impl VerboseBlock {
+    /// Rebuild the [`Block`] (require the transactions, so `getblock` verbosity >= 2)
+    pub fn to_block(&self) -> Result<Block, Error> {
+        let bits: u32 = u32::from_str_radix(&self.bits, 16)
+            .map_err(|e| Error::FailedToDeserialize(e.to_string()))?;
+        let header = Header {
+            version: Version::from_consensus(self.version),
+            prev_blockhash: self.previous_block_hash.unwrap_or(BlockHash::all_zeros()),
+            merkle_root: self.merkle_root,
+            time: self.time,
+            bits: CompactTarget::from_consensus(bits),
+            nonce: self.nonce,
+        };
+        let txdata = self
+            .tx
+            .iter()
+            .map(|tx| match tx {
+                VerboseBlockTx::Transaction(tx) => Ok(tx.transaction.clone()),
+                VerboseBlockTx::Txid(..) => Err(Error::BadResult),
+            })
+            .collect::<Result<Vec<Transaction>, Error>>()?;
+        Ok(Block { header, txdata })
+    }
+}
+
+/// Block with the txids only (`getblock` verbosity 1)
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockInfo {
+    pub hash: BlockHash,
+    /// `-1` if the block is not on the main chain
+    pub confirmations: i64,
+    pub height: u64,
+    pub version: i32,
+    #[serde(rename = "merkleroot")]
+    pub merkle_root: TxMerkleNode,
+    pub time: u32,
+    #[serde(rename = "mediantime")]
+    pub median_time: u64,
+    pub nonce: u32,
+    /// Compact target, hex encoded
+    pub bits: String,
+    pub difficulty: f64,
+    /// Hex encoded
+    #[serde(rename = "chainwork")]
+    pub chain_work: String,
+    #[serde(rename = "nTx")]
+    pub n_tx: u64,
+    pub size: u64,
+    #[serde(rename = "strippedsize")]
+    pub stripped_size: u64,
+    pub weight: u64,
+    pub tx: Vec<Txid>,
+    #[serde(rename = "previousblockhash")]
+    pub previous_block_hash: Option<BlockHash>,
+    #[serde(rename = "nextblockhash")]
+    pub next_block_hash: Option<BlockHash>,
+}
+
+#[derive(Debug, Clone)]
+pub enum GetBlockResponse {
+    /// Verbosity 0
+    Hex(String),
+    /// Verbosity 2
+    Decoded(Block),
+    /// Verbosity 1
+    Verbose(VerboseBlock),
+    /// Verbosity 3
+    VerboseWithPrevouts(VerboseBlock),
+}
+
+/// Deserialize consensus-encoded hex
+pub(crate) fn deserialize_hex<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: encode::Decodable,
+{
+    let hex: String = String::deserialize(deserializer)?;
+    encode::deserialize_hex(&hex).map_err(de::Error::custom)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceivedByAddress {
+    #[serde(default, rename = "involvesWatchonly")]
+    pub involves_watchonly: bool,
+    pub address: Address<NetworkUnchecked>,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub amount: Amount,
+    pub confirmations: u64,
+    pub label: String,
+    pub txids: Vec<Txid>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceivedByLabel {
+    #[serde(default, rename = "involvesWatchonly")]
+    pub involves_watchonly: bool,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub amount: Amount,
+    pub confirmations: u64,
+    pub label: String,
+}
+
+/// `scriptPubKey` of an `importmulti` request
+#[derive(Debug, Clone)]
+pub enum ImportMultiScriptPubKey {
+    /// Serialized as `{"address": "<address>"}`
+    Address(Address),
+    /// Serialized as hex
+    Script(ScriptBuf),
+}
+
+impl Serialize for ImportMultiScriptPubKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Address(address) => {
+                #[derive(Serialize)]
+                struct Object {
+                    address: String,
+                }
+
+                Object {
+                    address: address.to_string(),
+                }
+                .serialize(serializer)
+            }
+            Self::Script(script) => serializer.serialize_str(&script.to_hex_string()),
+        }
+    }
+}
+
+/// Key creation time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMultiTimestamp {
+    /// Skip rescan
+    Now,
+    /// UNIX timestamp
+    Time(u64),
+}
+
+impl Serialize for ImportMultiTimestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Now => serializer.serialize_str("now"),
+            Self::Time(timestamp) => serializer.serialize_u64(*timestamp),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportMultiRequest {
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ImportMultiScriptPubKey,
+    pub timestamp: ImportMultiTimestamp,
+    #[serde(rename = "redeemscript", skip_serializing_if = "Option::is_none")]
+    pub redeem_script: Option<ScriptBuf>,
+    #[serde(rename = "witnessscript", skip_serializing_if = "Option::is_none")]
+    pub witness_script: Option<ScriptBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pubkeys: Vec<PublicKey>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<PrivateKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<[u32; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal: Option<bool>,
+    #[serde(rename = "watchonly", skip_serializing_if = "Option::is_none")]
+    pub watch_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl ImportMultiRequest {
+    pub fn new(script_pub_key: ImportMultiScriptPubKey, timestamp: ImportMultiTimestamp) -> Self {
+        Self {
+            script_pub_key,
+            timestamp,
+            redeem_script: None,
+            witness_script: None,
+            pubkeys: Vec::new(),
+            keys: Vec::new(),
+            range: None,
+            internal: None,
+            watch_only: None,
+            label: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportMultiOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rescan: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportMultiError {
+    pub code: i32,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportMultiResult {
+    pub success: bool,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    pub error: Option<ImportMultiError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ZmqNotification {
+    /// Notification type (i.e. `pubhashblock`, `pubrawtx`)
+    #[serde(rename = "type")]
+    pub notification_type: String,
+    pub address: String,
+    /// Outbound message high water mark
+    pub hwm: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListUnspentQueryOptions {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "bitcoin::amount::serde::as_btc::opt"
+    )]
+    pub minimum_amount: Option<Amount>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "bitcoin::amount::serde::as_btc::opt"
+    )]
+    pub maximum_amount: Option<Amount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum_count: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "bitcoin::amount::serde::as_btc::opt"
+    )]
+    pub minimum_sum_amount: Option<Amount>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UnspentOutput {
+    pub txid: Txid,
+    pub vout: u32,
+    pub address: Option<Address<NetworkUnchecked>>,
+    pub label: Option<String>,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ScriptBuf,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub amount: Amount,
+    pub confirmations: u32,
+    /// Number of in-mempool ancestor transactions (including this one), if in mempool
+    #[serde(rename = "ancestorcount")]
+    pub ancestor_count: Option<u64>,
+    /// Virtual size of in-mempool ancestors (including this one), if in mempool
+    #[serde(rename = "ancestorsize")]
+    pub ancestor_size: Option<u64>,
+    /// Fees of in-mempool ancestors (including this one), if in mempool
+    #[serde(
+        default,
+        rename = "ancestorfees",
+        with = "bitcoin::amount::serde::as_sat::opt"
+    )]
+    pub ancestor_fees: Option<Amount>,
+    #[serde(rename = "redeemScript")]
+    pub redeem_script: Option<ScriptBuf>,
+    #[serde(rename = "witnessScript")]
+    pub witness_script: Option<ScriptBuf>,
+    pub spendable: bool,
+    pub solvable: bool,
+    /// Only present if the `avoid_reuse` wallet flag is set
+    pub reused: Option<bool>,
+    pub desc: Option<String>,
+    #[serde(default)]
+    pub parent_descs: Vec<String>,
+    pub safe: bool,
+}
+
+impl UnspentOutput {
+    pub fn outpoint(&self) -> OutPoint {
+        OutPoint::new(self.txid, self.vout)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockTemplateTransaction {
+    #[serde(rename = "data", deserialize_with = "deserialize_hex")]
+    pub transaction: Transaction,
+    pub txid: Txid,
+    #[serde(rename = "hash")]
+    pub wtxid: Wtxid,
+    /// 1-based indexes of the transactions this one depends on
+    pub depends: Vec<u32>,
+    #[serde(with = "bitcoin::amount::serde::as_sat")]
+    pub fee: Amount,
+    pub sigops: u64,
+    pub weight: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockTemplate {
+    pub version: i32,
+    pub rules: Vec<String>,
+    #[serde(rename = "previousblockhash")]
+    pub previous_block_hash: BlockHash,
+    pub transactions: Vec<BlockTemplateTransaction>,
+    #[serde(rename = "coinbasevalue", with = "bitcoin::amount::serde::as_sat")]
+    pub coinbase_value: Amount,
+    /// Pass it back to [`Client::get_block_template`](crate::Client::get_block_template) to long-poll for a new template
+    #[serde(rename = "longpollid")]
+    pub long_poll_id: String,
+    /// Hex encoded
+    pub target: String,
+    #[serde(rename = "mintime")]
+    pub min_time: u64,
+    pub mutable: Vec<String>,
+    #[serde(rename = "noncerange")]
+    pub nonce_range: String,
+    #[serde(rename = "sigoplimit")]
+    pub sigop_limit: u64,
+    #[serde(rename = "sizelimit")]
+    pub size_limit: u64,
+    #[serde(rename = "weightlimit")]
+    pub weight_limit: u64,
+    #[serde(rename = "curtime")]
+    pub cur_time: u64,
+    /// Compact target, hex encoded
+    pub bits: String,
+    pub height: u64,
+    pub default_witness_commitment: Option<ScriptBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct WalletDirEntry {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct WalletDir {
+    pub wallets: Vec<WalletDirEntry>,
+}
+
+/// Wallet warnings (`warning` string before Core v25, `warnings` array after)
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RawWalletWarnings {
+    #[serde(default, deserialize_with = "deserialize_warnings")]
+    warning: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_warnings")]
+    warnings: Vec<String>,
+}
+
+impl RawWalletWarnings {
+    fn into_warnings(self) -> Vec<String> {
+        if self.warnings.is_empty() {
+            self.warning
+        } else {
+            self.warnings
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RawLoadWalletResult {
+    name: String,
+    #[serde(flatten)]
+    warnings: RawWalletWarnings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "RawLoadWalletResult")]
+pub struct LoadWalletResult {
+    pub name: String,
+    pub warnings: Vec<String>,
+}
+
+impl From<RawLoadWalletResult> for LoadWalletResult {
+    fn from(raw: RawLoadWalletResult) -> Self {
+        Self {
+            name: raw.name,
+            warnings: raw.warnings.into_warnings(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "RawWalletWarnings")]
+pub struct UnloadWalletResult {
+    pub warnings: Vec<String>,
+}
+
+impl From<RawWalletWarnings> for UnloadWalletResult {
+    fn from(raw: RawWalletWarnings) -> Self {
+        Self {
+            warnings: raw.into_warnings(),
+        }
+    }
+}
+
+/// Deserialize warnings that can be either a string or an array of strings (Core v25+)
+///
+/// Empty strings are discarded.
+pub(crate) fn deserialize_warnings<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Warnings {
+        String(String),
+        Array(Vec<String>),
+    }
+
+    Ok(match Option::<Warnings>::deserialize(deserializer)? {
+        Some(Warnings::String(warning)) if warning.is_empty() => Vec::new(),
+        Some(Warnings::String(warning)) => vec![warning],
+        Some(Warnings::Array(warnings)) => warnings,
+        None => Vec::new(),
+    })
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MigrateWalletResult {
+    pub wallet_name: String,
+    /// Name of the watch-only wallet split off the migrated one, if any
+    pub watchonly_name: Option<String>,
+    /// Name of the solvables wallet split off the migrated one, if any
+    pub solvables_name: Option<String>,
+    /// Backup of the legacy wallet, taken before the migration
+    pub backup_path: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct GetTxOutResult {
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub value: Amount,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ScriptPubKey,
+}
+
+impl From<GetTxOutResult> for TxOut {
+    fn from(result: GetTxOutResult) -> Self {
+        Self {
+            value: result.value,
+            script_pubkey: result.script_pub_key.hex,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BumpFeeOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u16>,
+    /// Serialized as sat/vB
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "crate::fee::as_sat_per_vb::opt"
+    )]
+    pub fee_rate: Option<FeeRate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PsbtBumpFeeResult {
+    #[serde(deserialize_with = "deserialize_psbt")]
+    pub psbt: Psbt,
+    #[serde(rename = "origfee", with = "bitcoin::amount::serde::as_btc")]
+    pub original_fee: Amount,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub fee: Amount,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+/// Deserialize base64 encoded PSBT
+pub(crate) fn deserialize_psbt<'de, D>(deserializer: D) -> Result<Psbt, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let psbt: String = String::deserialize(deserializer)?;
+    Psbt::from_str(&psbt).map_err(de::Error::custom)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultisigResult {
+    pub address: Address<NetworkUnchecked>,
+    #[serde(rename = "redeemScript")]
+    pub redeem_script: ScriptBuf,
+    /// Output descriptor, with checksum
+    pub descriptor: String,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RescanBlockchainResult {
+    pub start_height: u64,
+    pub stop_height: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScanningProgress {
+    /// Elapsed seconds since scan start
+    pub duration: u64,
+    /// Scanning progress percentage (`0.0` to `1.0`)
+    pub progress: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletInfo {
+    #[serde(rename = "walletname")]
+    pub wallet_name: String,
+    #[serde(rename = "walletversion")]
+    pub wallet_version: u32,
+    /// Database format (`bdb` or `sqlite`)
+    pub format: String,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub balance: Amount,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub unconfirmed_balance: Amount,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub immature_balance: Amount,
+    #[serde(rename = "txcount")]
+    pub tx_count: u64,
+    /// Timestamp of the oldest keypool key (legacy wallets only)
+    #[serde(rename = "keypoololdest")]
+    pub keypool_oldest: Option<u64>,
+    #[serde(rename = "keypoolsize")]
+    pub keypool_size: u64,
+    /// Change keys in the keypool (legacy wallets only)
+    #[serde(rename = "keypoolsize_hd_internal")]
+    pub keypool_size_hd_internal: Option<u64>,
+    /// Timestamp until which the wallet is unlocked (`0` if locked, encrypted wallets only)
+    pub unlocked_until: Option<u64>,
+    #[serde(rename = "paytxfee", with = "crate::fee::as_btc_per_kvb")]
+    pub pay_tx_fee: FeeRate,
+    pub private_keys_enabled: bool,
+    pub avoid_reuse: bool,
+    /// `None` if not scanning
+    #[serde(default, deserialize_with = "deserialize_scanning")]
+    pub scanning: Option<ScanningProgress>,
+    /// If the wallet is a descriptor wallet
+    pub descriptors: bool,
+}
+
+/// Deserialize `scanning` field of `getwalletinfo` (`false` or an object)
+pub(crate) fn deserialize_scanning<'de, D>(
+    deserializer: D,
+) -> Result<Option<ScanningProgress>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Scanning {
+        Bool(bool),
+        Progress(ScanningProgress),
+    }
+
+    match Scanning::deserialize(deserializer)? {
+        Scanning::Bool(false) => Ok(None),
+        Scanning::Bool(true) => Err(de::Error::custom("unexpected scanning value: true")),
+        Scanning::Progress(progress) => Ok(Some(progress)),
+    }
+}
+
+/// Outputs of `send`-like RPCs
+#[derive(Debug, Clone, Default)]
+pub struct SendOutputs {
+    pub amounts: Vec<(Address, Amount)>,
+    /// `OP_RETURN` data
+    pub data: Option<Vec<u8>>,
+}
+
+impl Serialize for SendOutputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::{SerializeMap, SerializeSeq};
+
+        struct Entry<'a>(&'a str, serde_json::Value);
+
+        impl Serialize for Entry<'_> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(self.0, &self.1)?;
+                map.end()
+            }
+        }
+
+        let len: usize = self.amounts.len() + usize::from(self.data.is_some());
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        for (address, amount) in self.amounts.iter() {
+            seq.serialize_element(&Entry(&address.to_string(), amount.to_btc().into()))?;
+        }
+        if let Some(data) = &self.data {
+            seq.serialize_element(&Entry("data", data.to_lower_hex_string().into()))?;
+        }
+        seq.end()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SolvingData {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pubkeys: Vec<PublicKey>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<ScriptBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub descriptors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SendOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_to_wallet: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_position: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_type: Option<AddressType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+    /// Serialized as sat/vB
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "crate::fee::as_sat_per_vb::opt"
+    )]
+    pub fee_rate: Option<FeeRate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_watching: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_outpoints"
+    )]
+    pub inputs: Vec<OutPoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locktime: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_unspents: Option<bool>,
+    /// Always return a PSBT
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psbt: Option<bool>,
+    /// Indexes of the outputs to subtract the fee from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subtract_fee_from_outputs: Vec<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solving_data: Option<SolvingData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SendResult {
+    /// If the transaction has a complete set of signatures
+    pub complete: bool,
+    /// Only if the transaction is complete and added to the wallet
+    pub txid: Option<Txid>,
+    /// Only if the transaction is complete and not added to the wallet
+    #[serde(default, rename = "hex", deserialize_with = "deserialize_hex_opt")]
+    pub transaction: Option<Transaction>,
+    /// Only if the transaction is not complete or the `psbt` option is set
+    #[serde(default, deserialize_with = "deserialize_psbt_opt")]
+    pub psbt: Option<Psbt>,
+}
+
+/// Serialize [`OutPoint`]s as `{"txid": ..., "vout": ...}` objects
+pub(crate) fn serialize_outpoints<S>(
+    outpoints: &[OutPoint],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(outpoints.iter().copied().map(JsonOutPoint::from))
+}
+
+/// Deserialize optional consensus-encoded hex
+pub(crate) fn deserialize_hex_opt<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: encode::Decodable,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(hex) => encode::deserialize_hex(&hex)
+            .map(Some)
+            .map_err(de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// Deserialize optional base64 encoded PSBT
+pub(crate) fn deserialize_psbt_opt<'de, D>(deserializer: D) -> Result<Option<Psbt>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(psbt) => Psbt::from_str(&psbt).map(Some).map_err(de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// Recipient of `sendall`
+#[derive(Debug, Clone)]
+pub enum SendAllRecipient {
+    /// Receive an equal share of the remaining amount
+    Address(Address),
+    /// Receive a fixed amount
+    Amount(Address, Amount),
+}
+
+impl Serialize for SendAllRecipient {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        match self {
+            Self::Address(address) => address.serialize(serializer),
+            Self::Amount(address, amount) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(&address.to_string(), &amount.to_btc())?;
+                map.end()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SendAllOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_to_wallet: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+    /// Serialized as sat/vB
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "crate::fee::as_sat_per_vb::opt"
+    )]
+    pub fee_rate: Option<FeeRate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_watching: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_outpoints"
+    )]
+    pub inputs: Vec<OutPoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locktime: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_unspents: Option<bool>,
+    /// Always return a PSBT
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psbt: Option<bool>,
+    /// Spend only the UTXOs with a positive effective value at the fee rate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_max: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minconf: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxconf: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockReward {
+    pub subsidy: Amount,
+    /// Fees claimed by the coinbase (less than the actual fees if the miner claimed less than allowed)
+    pub fees: Amount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct BlockHeight {
+    pub height: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SendManyOptions {
+    pub minconf: Option<u32>,
+    pub comment: Option<String>,
+    /// Addresses to subtract the fee from (equally)
+    pub subtract_fee_from: Vec<Address<NetworkUnchecked>>,
+    pub replaceable: Option<bool>,
+    pub conf_target: Option<u16>,
+    pub estimate_mode: Option<EstimateMode>,
+    pub fee_rate: Option<FeeRate>,
+    /// Return also the fee reason
+    pub verbose: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SendManyResult {
+    pub txid: Txid,
+    /// Only with `verbose`
+    pub fee_reason: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for SendManyResult {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Txid(Txid),
+            Verbose { txid: Txid, fee_reason: String },
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Txid(txid) => Self {
+                txid,
+                fee_reason: None,
+            },
+            Raw::Verbose { txid, fee_reason } => Self {
+                txid,
+                fee_reason: Some(fee_reason),
+            },
+        })
+    }
+}
+
+/// Block height or hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(untagged)]
+pub enum BlockStatsTarget {
+    Height(u64),
+    Hash(BlockHash),
+}
+
+/// Block height or hash
+pub type HashOrHeight = BlockStatsTarget;
+
+/// Block statistics (only the requested stats are available)
+///
+/// Fees are in sat, fee rates in sat/vB.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BlockStats {
+    #[serde(rename = "avgfee")]
+    pub avg_fee: Option<u64>,
+    #[serde(rename = "avgfeerate")]
+    pub avg_fee_rate: Option<u64>,
+    #[serde(rename = "avgtxsize")]
+    pub avg_tx_size: Option<u64>,
+    #[serde(rename = "blockhash")]
+    pub block_hash: Option<BlockHash>,
+    /// 10th, 25th, 50th, 75th and 90th percentile fee rates
+    pub feerate_percentiles: Option<[u64; 5]>,
+    pub height: Option<u64>,
+    pub ins: Option<u64>,
+    #[serde(rename = "maxfee")]
+    pub max_fee: Option<u64>,
+    #[serde(rename = "maxfeerate")]
+    pub max_fee_rate: Option<u64>,
+    #[serde(rename = "maxtxsize")]
+    pub max_tx_size: Option<u64>,
+    #[serde(rename = "medianfee")]
+    pub median_fee: Option<u64>,
+    #[serde(rename = "mediantime")]
+    pub median_time: Option<u64>,
+    #[serde(rename = "mediantxsize")]
+    pub median_tx_size: Option<u64>,
+    #[serde(rename = "minfee")]
+    pub min_fee: Option<u64>,
+    #[serde(rename = "minfeerate")]
+    pub min_fee_rate: Option<u64>,
+    #[serde(rename = "mintxsize")]
+    pub min_tx_size: Option<u64>,
+    pub outs: Option<u64>,
+    pub subsidy: Option<u64>,
+    #[serde(rename = "swtotal_size")]
+    pub segwit_total_size: Option<u64>,
+    #[serde(rename = "swtotal_weight")]
+    pub segwit_total_weight: Option<u64>,
+    #[serde(rename = "swtxs")]
+    pub segwit_txs: Option<u64>,
+    pub time: Option<u64>,
+    pub total_out: Option<u64>,
+    pub total_size: Option<u64>,
+    pub total_weight: Option<u64>,
+    #[serde(rename = "totalfee")]
+    pub total_fee: Option<u64>,
+    pub txs: Option<u64>,
+    pub utxo_increase: Option<i64>,
+    pub utxo_size_inc: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SendToAddressOptions {
+    pub comment: Option<String>,
+    pub comment_to: Option<String>,
+    pub subtract_fee_from_amount: Option<bool>,
+    pub replaceable: Option<bool>,
+    pub conf_target: Option<u16>,
+    pub estimate_mode: Option<EstimateMode>,
+    pub avoid_reuse: Option<bool>,
+    pub fee_rate: Option<FeeRate>,
+    /// Return also the fee reason
+    pub verbose: Option<bool>,
+}
+
+/// Same shapes of `sendmany` result (`txid` or `{txid, fee_reason}`)
+pub type SendToAddressResult = SendManyResult;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WalletFlag {
+    AvoidReuse,
+    /// Flag not known by this version of the crate
+    Other(String),
+}
+
+impl WalletFlag {
+    /// Raw flag name, as used by Bitcoin Core
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::AvoidReuse => "avoid_reuse",
+            Self::Other(flag) => flag.as_str(),
+        }
+    }
+}
+
+impl From<&str> for WalletFlag {
+    fn from(flag: &str) -> Self {
+        match flag {
+            "avoid_reuse" => Self::AvoidReuse,
+            flag => Self::Other(flag.to_string()),
+        }
+    }
+}
+
+impl Serialize for WalletFlag {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for WalletFlag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let flag: String = String::deserialize(deserializer)?;
+        Ok(Self::from(flag.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetWalletFlagResult {
+    pub flag_name: WalletFlag,
+    pub flag_state: bool,
+    #[serde(default, deserialize_with = "deserialize_warnings")]
+    pub warnings: Vec<String>,
+}
+
+/// Signature hash type, as accepted by the signing RPCs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum SighashType {
+    /// `ALL` for ECDSA, `DEFAULT` for taproot
+    #[serde(rename = "DEFAULT")]
+    Default,
+    #[serde(rename = "ALL")]
+    All,
+    #[serde(rename = "NONE")]
+    None,
+    #[serde(rename = "SINGLE")]
+    Single,
+    #[serde(rename = "ALL|ANYONECANPAY")]
+    AllPlusAnyoneCanPay,
+    #[serde(rename = "NONE|ANYONECANPAY")]
+    NonePlusAnyoneCanPay,
+    #[serde(rename = "SINGLE|ANYONECANPAY")]
+    SinglePlusAnyoneCanPay,
+}
+
+/// Previous output not known by the node (i.e. not yet in the chain or in the mempool)
+#[derive(Debug, Clone, Serialize)]
+pub struct PrevTx {
+    pub txid: Txid,
+    pub vout: u32,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: ScriptBuf,
+    #[serde(rename = "redeemScript", skip_serializing_if = "Option::is_none")]
+    pub redeem_script: Option<ScriptBuf>,
+    #[serde(rename = "witnessScript", skip_serializing_if = "Option::is_none")]
+    pub witness_script: Option<ScriptBuf>,
+    /// Required for segwit outputs
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "bitcoin::amount::serde::as_btc::opt"
+    )]
+    pub amount: Option<Amount>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignError {
+    pub txid: Txid,
+    pub vout: u32,
+    /// Hex-encoded witness items
+    #[serde(default)]
+    pub witness: Vec<String>,
+    #[serde(rename = "scriptSig")]
+    pub script_sig: ScriptBuf,
+    pub sequence: u32,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignResult {
+    #[serde(rename = "hex", deserialize_with = "deserialize_hex")]
+    pub transaction: Transaction,
+    /// `false` if the transaction is only partially signed (not an error, i.e. multisig)
+    pub complete: bool,
+    #[serde(default)]
+    pub errors: Vec<SignError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct SimulateRawTransactionResult {
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub balance_change: SignedAmount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpgradeWalletResult {
+    pub wallet_name: String,
+    pub previous_version: u32,
+    pub current_version: u32,
+    /// Description of the result, if the wallet was upgraded (or already up to date)
+    pub result: Option<String>,
+    /// Error message, if the upgrade failed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateTxInput {
+    pub txid: Txid,
+    pub vout: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u32>,
+}
+
+/// Outputs of `createrawtransaction`-like RPCs (same shape of `send` ones)
+pub type CreateTxOutputs = SendOutputs;
+
+/// Options of `fundrawtransaction` and `walletcreatefundedpsbt`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FundOptions {
+    /// Add more inputs if the given ones aren't enough
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_inputs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_unsafe: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_position: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_type: Option<AddressType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_watching: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_unspents: Option<bool>,
+    /// Serialized as sat/vB
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "crate::fee::as_sat_per_vb::opt"
+    )]
+    pub fee_rate: Option<FeeRate>,
+    /// Indexes of the outputs to subtract the fee from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subtract_fee_from_outputs: Vec<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaceable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf_target: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_mode: Option<EstimateMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solving_data: Option<SolvingData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletCreateFundedPsbtResult {
+    #[serde(deserialize_with = "deserialize_psbt")]
+    pub psbt: Psbt,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    pub fee: Amount,
+    /// Position of the change output, `-1` if none
+    #[serde(rename = "changepos")]
+    pub change_position: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct WalletDisplayAddressResult {
+    pub address: Address<NetworkUnchecked>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletProcessPsbtResult {
+    #[serde(deserialize_with = "deserialize_psbt")]
+    pub psbt: Psbt,
+    /// If the transaction has a complete set of signatures
+    pub complete: bool,
+    /// Only if the PSBT is complete and finalized (Core v26+)
+    #[serde(default, rename = "hex", deserialize_with = "deserialize_hex_opt")]
+    pub transaction: Option<Transaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalSigner {
+    /// Master key fingerprint (hex)
+    pub fingerprint: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct EnumerateSignersResult {
+    pub signers: Vec<ExternalSigner>,
+}
+
+/// Output descriptor to scan for (i.e. in `utxoupdatepsbt`)
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ScanObject {
+    Descriptor(String),
+    /// Ranged descriptor, with the `[begin, end]` range of the child indexes
+    Ranged {
+        desc: String,
+        range: (u32, u32),
+    },
+}
+
+/// Result of the `waitfor*` RPCs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct WaitForBlockResult {
+    pub hash: BlockHash,
+    pub height: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::mock::fixture;
+
+    #[test]
+    fn test_chain() {
+        for (name, network) in [
+            ("main", Network::Bitcoin),
+            ("test", Network::Testnet),
+            ("testnet4", Network::Testnet4),
+            ("signet", Network::Signet),
+            ("regtest", Network::Regtest),
+        ] {
+            let chain: Chain = serde_json::from_value(json!(name)).unwrap();
+            assert_eq!(chain, Chain::Network(network));
+            assert_eq!(chain.network(), Some(network));
+            assert_eq!(chain.as_str(), name);
+        }
+
+        let chain: Chain = serde_json::from_value(json!("liquidv1")).unwrap();
+        assert_eq!(chain, Chain::Unknown(String::from("liquidv1")));
+        assert_eq!(chain.network(), None);
+        assert_eq!(chain.as_str(), "liquidv1");
+    }
+
+    #[test]
+    fn test_chain_fields() {
+        let info: BlockchainInfo =
+            serde_json::from_value(fixture("getblockchaininfo.json")).unwrap();
+        assert_eq!(info.chain, Chain::Network(Network::Bitcoin));
+
+        let info: MiningInfo = serde_json::from_value(json!({
+            "blocks": 200,
+            "difficulty": 4.656542373906925e-10,
+            "networkhashps": 0.0,
+            "pooledtx": 0,
+            "chain": "regtest",
+            "warnings": "",
+        }))
+        .unwrap();
+        assert_eq!(info.chain, Chain::Network(Network::Regtest));
+    }
+
+    #[test]
+    fn test_label_enums() {
+        for (address_type, name) in [
+            (AddressType::Legacy, "legacy"),
+            (AddressType::P2shSegwit, "p2sh-segwit"),
+            (AddressType::Bech32, "bech32"),
+            (AddressType::Bech32m, "bech32m"),
+        ] {
+            assert_eq!(serde_json::to_value(address_type).unwrap(), json!(name));
+            assert_eq!(
+                serde_json::from_value::<AddressType>(json!(name)).unwrap(),
+                address_type
+            );
+        }
+
+        assert_eq!(
+            serde_json::to_value(LabelPurpose::Send).unwrap(),
+            json!("send")
+        );
+        assert_eq!(
+            serde_json::to_value(LabelPurpose::Receive).unwrap(),
+            json!("receive")
+        );
+    }
+
+    #[test]
+    fn test_list_descriptors() {
+        let res: ListDescriptorsResult =
+            serde_json::from_value(fixture("listdescriptors.json")).unwrap();
+        assert_eq!(res.wallet_name, "hot");
+
+        // Ranged
+        let ranged: &WalletDescriptor = &res.descriptors[0];
+        assert!(ranged.active);
+        assert_eq!(ranged.internal, Some(false));
+        assert_eq!(ranged.range, Some([0, 999]));
+        assert_eq!(ranged.next_index, Some(12));
+
+        // Non-ranged
+        let single: &WalletDescriptor = &res.descriptors[1];
+        assert!(single.desc.starts_with("addr("));
+        assert!(!single.active);
+        assert_eq!(single.internal, None);
+        assert_eq!(single.range, None);
+        assert_eq!(single.next, None);
+    }
+
+    #[test]
+    fn test_send_serialization() {
+        let address = Address::from_str("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")
+            .unwrap()
+            .assume_checked();
+        let outputs = SendOutputs {
+            amounts: vec![(address.clone(), Amount::from_sat(150_000))],
+            data: Some(b"hello".to_vec()),
+        };
+        assert_eq!(
+            serde_json::to_value(&outputs).unwrap(),
+            json!([
+                { "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080": 0.0015 },
+                { "data": "68656c6c6f" },
+            ])
+        );
+
+        // Unset options are omitted
+        assert_eq!(
+            serde_json::to_value(SendOptions::default()).unwrap(),
+            json!({})
+        );
+
+        let txid = Txid::from_str(&"a1".repeat(32)).unwrap();
+        let options = SendOptions {
+            add_to_wallet: Some(false),
+            change_address: Some(address),
+            change_type: Some(AddressType::Bech32m),
+            estimate_mode: Some(EstimateMode::Economical),
+            fee_rate: Some(FeeRate::from_sat_per_vb_u32(12)),
+            inputs: vec![OutPoint::new(txid, 1)],
+            subtract_fee_from_outputs: vec![0],
+            replaceable: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&options).unwrap(),
+            json!({
+                "add_to_wallet": false,
+                "change_address": "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+                "change_type": "bech32m",
+                "estimate_mode": "ECONOMICAL",
+                "fee_rate": 12.0,
+                "inputs": [{ "txid": txid, "vout": 1 }],
+                "subtract_fee_from_outputs": [0],
+                "replaceable": true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_tip() {
+        let info: BlockchainInfo =
+            serde_json::from_value(fixture("getblockchaininfo.json")).unwrap();
+        let tip = Tip::from(&info);
+        assert_eq!(tip.height, 850_000);
+        assert_eq!(tip.header_height, 850_000);
+        assert_eq!(
+            tip.hash,
+            BlockHash::from_str("00000000000000000002a0b5db2a7f8d9087464c2586b546be7bce8eb53b8187")
+                .unwrap()
+        );
+
+        // Headers ahead during the initial block download
+        let mut info = fixture("getblockchaininfo.json");
+        info["blocks"] = json!(420_000);
+        info["headers"] = json!(850_000);
+        let info: BlockchainInfo = serde_json::from_value(info).unwrap();
+        let tip = Tip::from(&info);
+        assert_eq!(tip.height, 420_000);
+        assert_eq!(tip.header_height, 850_000);
+    }
+
+    #[test]
+    fn test_warnings() {
+        fn warnings<T>(name: &str, warnings: Option<Value>) -> T
+        where
+            T: de::DeserializeOwned,
+        {
+            let mut info = fixture(name);
+            match warnings {
+                Some(warnings) => info["warnings"] = warnings,
+                None => {
+                    info.as_object_mut().unwrap().remove("warnings");
+                }
+            }
+            serde_json::from_value(info).unwrap()
+        }
+
+        let message = "This is a pre-release test build";
+        for (value, expected) in [
+            // Before v28
+            (Some(json!("")), Vec::new()),
+            (Some(json!(message)), vec![message.to_string()]),
+            // v28+
+            (Some(json!([])), Vec::new()),
+            (
+                Some(json!([message, "Disk space is low!"])),
+                vec![message.to_string(), "Disk space is low!".to_string()],
+            ),
+            (None, Vec::new()),
+        ] {
+            let info: BlockchainInfo = warnings("getblockchaininfo.json", value.clone());
+            assert_eq!(info.warnings, expected);
+            let info: NetworkInfo = warnings("getnetworkinfo.json", value.clone());
+            assert_eq!(info.warnings, expected);
+            let info: MiningInfo = warnings("getmininginfo.json", value);
+            assert_eq!(info.warnings, expected);
+        }
+    }
+
+    #[test]
+    fn test_mining_info() {
+        // No block template generated yet
+        let info: MiningInfo = serde_json::from_value(fixture("getmininginfo.json")).unwrap();
+        assert_eq!(info.blocks, 850_000);
+        assert_eq!(info.chain.network(), Some(Network::Bitcoin));
+        assert_eq!(info.pooled_tx, 41_374);
+        assert_eq!(info.current_block_weight, None);
+        assert_eq!(info.current_block_tx, None);
+
+        let mut info = fixture("getmininginfo.json");
+        info["currentblockweight"] = json!(3_992_864);
+        info["currentblocktx"] = json!(3_173);
+        let info: MiningInfo = serde_json::from_value(info).unwrap();
+        assert_eq!(info.current_block_weight, Some(3_992_864));
+        assert_eq!(info.current_block_tx, Some(3_173));
+    }
+
+    #[test]
+    fn test_wallet_info_scanning() {
+        let info: WalletInfo = serde_json::from_value(fixture("getwalletinfo.json")).unwrap();
+        assert_eq!(info.wallet_name, "hot");
+        assert_eq!(info.balance, Amount::from_sat(125_000_000));
+        assert_eq!(info.unlocked_until, Some(0));
+        assert_eq!(info.pay_tx_fee, FeeRate::ZERO);
+        assert!(info.scanning.is_none());
+        assert!(info.descriptors);
+
+        let mut info = fixture("getwalletinfo.json");
+        info["scanning"] = json!({ "duration": 12, "progress": 0.4375 });
+        let info: WalletInfo = serde_json::from_value(info).unwrap();
+        let scanning = info.scanning.unwrap();
+        assert_eq!(scanning.duration, 12);
+        assert_eq!(scanning.progress, 0.4375);
+
+        // Not sent by old versions
+        let mut info = fixture("getwalletinfo.json");
+        info.as_object_mut().unwrap().remove("scanning");
+        let info: WalletInfo = serde_json::from_value(info).unwrap();
+        assert!(info.scanning.is_none());
+
+        let mut info = fixture("getwalletinfo.json");
+        info["scanning"] = json!(true);
+        assert!(serde_json::from_value::<WalletInfo>(info).is_err());
+    }
+
+    #[test]
+    fn test_core_feature() {
+        // v20.2
+        assert!(!CoreFeature::Send.is_supported(200200));
+        // v21.0
+        assert!(CoreFeature::Send.is_supported(210000));
+        assert!(!CoreFeature::SendAll.is_supported(230200));
+        // v24.0.1
+        assert!(CoreFeature::SendAll.is_supported(240001));
+        assert!(CoreFeature::MigrateWallet.is_supported(240001));
+        assert!(!CoreFeature::ScanBlocks.is_supported(240001));
+        // v27.0
+        assert!(CoreFeature::ScanBlocks.is_supported(270000));
+        assert!(!CoreFeature::SubmitPackage.is_supported(270000));
+        assert!(!CoreFeature::GetDescriptorActivity.is_supported(280100));
+        assert!(CoreFeature::GetDescriptorActivity.is_supported(290000));
+    }
+
+    #[test]
+    fn test_latency_summary() {
+        let peers: Vec<PeerInfo> = serde_json::from_value(json!([
+            { "id": 0, "addr": "203.0.113.1:8333", "network": "ipv4", "pingtime": 0.3 },
+            { "id": 1, "addr": "203.0.113.2:8333", "network": "ipv4", "pingtime": 0.1 },
+            // Not measured yet
+            { "id": 2, "addr": "203.0.113.3:8333", "network": "ipv4" },
+            { "id": 3, "addr": "[2001:db8::1]:8333", "network": "ipv6", "pingtime": 0.2 },
+            { "id": 4, "addr": "abc.onion:8333", "network": "onion", "pingtime": 1.5 },
+        ]))
+        .unwrap();
+
+        let summary = LatencySummary::from_peers(&peers);
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.min, Duration::from_millis(100));
+        // Even count: mean of the two middle values
+        assert_eq!(summary.median, Duration::from_millis(250));
+        assert_eq!(summary.max, Duration::from_millis(1_500));
+
+        let summary = LatencySummary::from_peers(&peers[..3]);
+        assert_eq!(summary.count, 2);
+        assert_eq!(summary.median, Duration::from_millis(200));
+
+        let summary = LatencySummary::from_peers(&peers[2..3]);
+        assert_eq!(
+            summary,
+            LatencySummary {
+                count: 0,
+                min: Duration::ZERO,
+                median: Duration::ZERO,
+                max: Duration::ZERO,
+            }
+        );
+    }
+}
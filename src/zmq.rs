@@ -0,0 +1,231 @@
+// Copyright (c) 2021-2024 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! ZMQ notifications subscriber
+//!
+//! Bitcoin Core must be started with the matching `-zmqpub<topic>=<endpoint>` options
+//! (see [`Client::get_zmq_notifications`](crate::Client::get_zmq_notifications)).
+
+use std::collections::HashMap;
+
+use bitcoin::consensus::encode;
+use bitcoin::hashes::Hash;
+use bitcoin::{Block, BlockHash, Transaction, Txid};
+
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZmqTopic {
+    HashBlock,
+    HashTx,
+    RawBlock,
+    RawTx,
+}
+
+impl ZmqTopic {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::HashBlock => "hashblock",
+            Self::HashTx => "hashtx",
+            Self::RawBlock => "rawblock",
+            Self::RawTx => "rawtx",
+        }
+    }
+
+    fn from_bytes(topic: &[u8]) -> Option<Self> {
+        match topic {
+            b"hashblock" => Some(Self::HashBlock),
+            b"hashtx" => Some(Self::HashTx),
+            b"rawblock" => Some(Self::RawBlock),
+            b"rawtx" => Some(Self::RawTx),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ZmqMessage {
+    HashBlock(BlockHash),
+    HashTx(Txid),
+    RawBlock(Block),
+    RawTx(Transaction),
+}
+
+impl ZmqMessage {
+    pub fn topic(&self) -> ZmqTopic {
+        match self {
+            Self::HashBlock(..) => ZmqTopic::HashBlock,
+            Self::HashTx(..) => ZmqTopic::HashTx,
+            Self::RawBlock(..) => ZmqTopic::RawBlock,
+            Self::RawTx(..) => ZmqTopic::RawTx,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ZmqEvent {
+    pub message: ZmqMessage,
+    /// Per-topic sequence number
+    pub sequence: u32,
+    /// Number of messages of the same topic missed since the previous one (i.e. dropped due to the high water mark)
+    pub missed: u32,
+}
+
+/// Decode a `[topic, body, sequence]` multipart message
+///
+/// [`ZmqEvent::missed`] is always `0`: gap detection is done by [`ZmqSubscriber`].
+pub fn decode(frames: &[Vec<u8>]) -> Result<ZmqEvent, Error> {
+    let [topic, body, sequence] = frames else {
+        return Err(Error::FailedToDeserialize(format!(
+            "expected 3 frames, got {}",
+            frames.len()
+        )));
+    };
+
+    let topic = ZmqTopic::from_bytes(topic).ok_or_else(|| {
+        Error::FailedToDeserialize(format!("unknown topic: {}", String::from_utf8_lossy(topic)))
+    })?;
+
+    let sequence: [u8; 4] = sequence.as_slice().try_into().map_err(|_| {
+        Error::FailedToDeserialize(format!("invalid sequence length: {}", sequence.len()))
+    })?;
+
+    let message = match topic {
+        ZmqTopic::HashBlock => ZmqMessage::HashBlock(BlockHash::from_byte_array(hash(body)?)),
+        ZmqTopic::HashTx => ZmqMessage::HashTx(Txid::from_byte_array(hash(body)?)),
+        ZmqTopic::RawBlock => ZmqMessage::RawBlock(
+            encode::deserialize(body).map_err(|e| Error::FailedToDeserialize(e.to_string()))?,
+        ),
+        ZmqTopic::RawTx => ZmqMessage::RawTx(
+            encode::deserialize(body).map_err(|e| Error::FailedToDeserialize(e.to_string()))?,
+        ),
+    };
+
+    Ok(ZmqEvent {
+        message,
+        sequence: u32::from_le_bytes(sequence),
+        missed: 0,
+    })
+}
+
+/// Hashes are published in reversed (RPC) byte order
+fn hash(body: &[u8]) -> Result<[u8; 32], Error> {
+    let mut hash: [u8; 32] = body
+        .try_into()
+        .map_err(|_| Error::FailedToDeserialize(format!("invalid hash length: {}", body.len())))?;
+    hash.reverse();
+    Ok(hash)
+}
+
+pub struct ZmqSubscriber {
+    socket: ::zmq::Socket,
+    last_sequence: HashMap<ZmqTopic, u32>,
+}
+
+impl ZmqSubscriber {
+    /// Connect to a ZMQ endpoint (i.e. `tcp://127.0.0.1:28332`)
+    pub fn connect(endpoint: &str) -> Result<Self, Error> {
+        let context = ::zmq::Context::new();
+        let socket = context.socket(::zmq::SUB)?;
+        socket.connect(endpoint)?;
+        Ok(Self {
+            socket,
+            last_sequence: HashMap::new(),
+        })
+    }
+
+    pub fn subscribe(&self, topic: ZmqTopic) -> Result<(), Error> {
+        Ok(self.socket.set_subscribe(topic.as_str().as_bytes())?)
+    }
+
+    pub fn subscribe_hash_block(&self) -> Result<(), Error> {
+        self.subscribe(ZmqTopic::HashBlock)
+    }
+
+    pub fn subscribe_hash_tx(&self) -> Result<(), Error> {
+        self.subscribe(ZmqTopic::HashTx)
+    }
+
+    pub fn subscribe_raw_block(&self) -> Result<(), Error> {
+        self.subscribe(ZmqTopic::RawBlock)
+    }
+
+    pub fn subscribe_raw_tx(&self) -> Result<(), Error> {
+        self.subscribe(ZmqTopic::RawTx)
+    }
+
+    /// Block until the next notification is received
+    pub fn recv(&mut self) -> Result<ZmqEvent, Error> {
+        let frames: Vec<Vec<u8>> = self.socket.recv_multipart(0)?;
+        let mut event: ZmqEvent = decode(&frames)?;
+
+        if let Some(last) = self
+            .last_sequence
+            .insert(event.message.topic(), event.sequence)
+        {
+            event.missed = event.sequence.wrapping_sub(last.wrapping_add(1));
+        }
+
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::blockdata::constants::genesis_block;
+    use bitcoin::hashes::hex::FromHex;
+    use bitcoin::Network;
+
+    use super::*;
+
+    fn frames(topic: &str, body: Vec<u8>, sequence: u32) -> Vec<Vec<u8>> {
+        vec![
+            topic.as_bytes().to_vec(),
+            body,
+            sequence.to_le_bytes().to_vec(),
+        ]
+    }
+
+    #[test]
+    fn test_decode_hash_block() {
+        // Genesis block hash, as published by Core
+        let body =
+            Vec::from_hex("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f")
+                .unwrap();
+        let event = decode(&frames("hashblock", body, 7)).unwrap();
+        assert_eq!(event.sequence, 7);
+        assert_eq!(event.missed, 0);
+        match event.message {
+            ZmqMessage::HashBlock(hash) => {
+                assert_eq!(hash, genesis_block(Network::Bitcoin).block_hash())
+            }
+            message => panic!("unexpected message: {message:?}"),
+        }
+    }
+
+    #[test]
+    fn test_decode_raw() {
+        let genesis = genesis_block(Network::Bitcoin);
+
+        let event = decode(&frames("rawblock", encode::serialize(&genesis), 0)).unwrap();
+        assert_eq!(event.message.topic(), ZmqTopic::RawBlock);
+        assert!(matches!(event.message, ZmqMessage::RawBlock(block) if block == genesis));
+
+        let tx = genesis.txdata[0].clone();
+        let event = decode(&frames("rawtx", encode::serialize(&tx), u32::MAX)).unwrap();
+        assert_eq!(event.sequence, u32::MAX);
+        assert!(matches!(event.message, ZmqMessage::RawTx(raw) if raw == tx));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert!(decode(&frames("hashtx", vec![0; 32], 0)[..2]).is_err());
+        assert!(decode(&frames("sequence", vec![0; 33], 0)).is_err());
+        assert!(decode(&frames("hashtx", vec![0; 31], 0)).is_err());
+        assert!(decode(&frames("rawtx", vec![0xff; 4], 0)).is_err());
+
+        let mut invalid = frames("hashtx", vec![0; 32], 0);
+        invalid[2].pop();
+        assert!(decode(&invalid).is_err());
+    }
+}